@@ -0,0 +1,41 @@
+// Times A*, Dijkstra and BFS on every bundled maze, so a change that slows the search down shows up in the numbers
+// Run with: cargo bench --bench algorithms
+use std::{path::Path, time::{Duration, Instant}};
+
+use mazesolver::{solve_with, Algorithm, Cell, Connectivity, Grid, Heuristic, SearchStats};
+
+const MAZES: [&str; 5] = ["maze-Easy", "maze-Medium", "maze-Large", "maze-VLarge", "maze-VLarge2"];
+const ALGORITHMS: [(&str, Algorithm); 3] = [("A*", Algorithm::AStar), ("Dijkstra", Algorithm::Dijkstra), ("BFS", Algorithm::Bfs)];
+// Small mazes solve in microseconds, so each one is solved again and again until this much time has gone by,
+// and the average taken; big ones still get at least MIN_RUNS
+const MIN_TIME: Duration = Duration::from_millis(500);
+const MIN_RUNS: u32 = 3;
+
+// The average time a solve takes, along with the statistics from the last one
+// The clone happens outside the timing, so every run starts from a fresh grid without paying for copying it
+fn time_runs(maze: &Grid<Cell>, algorithm: Algorithm) -> (Duration, SearchStats) {
+    let mut stats = SearchStats::default();
+    let mut total = Duration::ZERO;
+    let mut runs = 0;
+    while runs < MIN_RUNS || total < MIN_TIME {
+        let mut grid = maze.clone();
+        let start_time = Instant::now();
+        stats = solve_with(&mut grid, algorithm, Connectivity::Four, Heuristic::Manhattan, None).1;
+        total += start_time.elapsed();
+        runs += 1;
+    }
+    (total / runs, stats)
+}
+
+fn main() {
+    println!("{:<14} {:<10} {:>14} {:>12} {:>16}", "maze", "algorithm", "time per solve", "nodes", "nodes per second");
+    for name in MAZES {
+        let path = format!("mazes/{}.txt", name);
+        let maze = Grid::new(Path::new(&path)).unwrap_or_else(|error| panic!("couldn't load {}: {}", path, error));
+        for (algorithm_name, algorithm) in ALGORITHMS {
+            let (time, stats) = time_runs(&maze, algorithm);
+            let nodes_per_second = stats.nodes_expanded as f64 / time.as_secs_f64();
+            println!("{:<14} {:<10} {:>14.2?} {:>12} {:>16.0}", name, algorithm_name, time, stats.nodes_expanded, nodes_per_second);
+        }
+    }
+}
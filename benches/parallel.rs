@@ -0,0 +1,28 @@
+// Compares single-threaded A* with the parallel breadth-first search on the largest bundled maze
+// Run with: cargo bench --features parallel --bench parallel
+use std::{path::Path, time::{Duration, Instant}};
+
+use mazesolver::{solve_parallel, solve_with, Algorithm, Connectivity, Grid, Heuristic};
+
+const RUNS: u32 = 10;
+
+// The average time a solve takes over RUNS runs, along with the length of the path it found
+fn time_runs(mut run: impl FnMut() -> usize) -> (Duration, usize) {
+    let mut path_length = 0;
+    let start_time = Instant::now();
+    for _ in 0..RUNS {
+        path_length = run();
+    }
+    (start_time.elapsed() / RUNS, path_length)
+}
+
+fn main() {
+    let maze = Grid::new(Path::new("mazes/maze-VLarge2.txt")).expect("couldn't load the benchmark maze");
+    let (astar_time, astar_length) = time_runs(|| {
+        let mut grid = maze.clone();
+        solve_with(&mut grid, Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, None).1.path_length
+    });
+    let (parallel_time, parallel_length) = time_runs(|| solve_parallel(&maze, Connectivity::Four).1.path_length);
+    println!("A*:           {:?} per solve, path length {}", astar_time, astar_length);
+    println!("parallel BFS: {:?} per solve, path length {} ({} threads)", parallel_time, parallel_length, rayon::current_num_threads());
+}
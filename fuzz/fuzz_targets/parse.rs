@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mazesolver::{solve, Grid, GridConfig};
+
+// Any bytes at all should load as a maze or fail with a MazeError, never panic
+// Whatever loads should solve without panicking too
+fuzz_target!(|data: &[u8]| {
+    if let Ok(mut maze) = Grid::from_reader(data) {
+        solve(&mut maze);
+    }
+    let config = GridConfig { comment_prefix: Some("# ".to_string()), ..GridConfig::default() };
+    let _ = Grid::from_reader_with_config(data, &config);
+    // Whichever format the bytes look like: JSON, CSV and thin walls as well as the text format
+    if let Ok(mut maze) = Grid::from_reader_in(data, None, &GridConfig::default()) {
+        solve(&mut maze);
+    }
+});
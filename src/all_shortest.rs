@@ -0,0 +1,123 @@
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, solve::{Connectivity}};
+
+// Every shortest path from the entrance to an exit, entrance first, rather than the single one solve picks between them
+// Runs Dijkstra, but instead of one parent per cell it keeps every neighbour that reaches the cell at its cheapest cost,
+// then walks back through all of those parents from each of the cheapest exits to list the paths
+// The number of shortest paths can grow exponentially with the size of an open maze, so max_paths caps how many are returned;
+// None returns all of them. Returns no paths at all if no exit can be reached
+pub fn solve_all_shortest(grid: &Grid<Cell>, connectivity: Connectivity, max_paths: Option<usize>) -> Vec<Vec<Coordinate>> {
+    let mut open_set = BinaryHeap::new();
+    let mut best_costs = HashMap::new();
+    // Every neighbour that reaches each cell at its best cost
+    let mut predecessors: HashMap<Coordinate, Vec<Coordinate>> = HashMap::new();
+    // The cost of the cheapest exit, once we've popped it, and every exit that costs the same
+    let mut best_exit_cost = None;
+    let mut best_exits = Vec::new();
+
+    best_costs.insert(grid.entrance_location, 0);
+    open_set.push(grid[grid.entrance_location]);
+    while let Some(current_cell) = open_set.pop() {
+        if current_cell.cost > best_costs[&current_cell.coordinate] {
+            continue;
+        }
+        // Cells come out cheapest first, so once they cost more than the cheapest exit, no more shortest paths can be found
+        if best_exit_cost.is_some_and(|best_exit_cost| current_cell.cost > best_exit_cost) {
+            break;
+        }
+        if grid.exits.contains(&current_cell.coordinate) {
+            best_exit_cost = Some(current_cell.cost);
+            best_exits.push(current_cell.coordinate);
+            continue;
+        }
+        for neighbour in grid.neighbours(current_cell.coordinate, connectivity) {
+            let mut neighbour_cell = grid[neighbour];
+            if neighbour_cell.cell_type == CellType::Wall || neighbour == grid.entrance_location {
+                continue;
+            }
+            let tentative_cost = current_cell.cost + connectivity.step_cost_between(grid, current_cell.coordinate, neighbour) * neighbour_cell.weight;
+            match best_costs.get(&neighbour) {
+                // A route as cheap as the best one so far is another shortest route, so remember where it came from too
+                Some(&best_cost) if tentative_cost == best_cost => {
+                    predecessors.get_mut(&neighbour).unwrap().push(current_cell.coordinate);
+                },
+                Some(&best_cost) if tentative_cost > best_cost => (),
+                // A cheaper route makes every route found before it not the shortest any more
+                _ => {
+                    best_costs.insert(neighbour, tentative_cost);
+                    predecessors.insert(neighbour, vec![current_cell.coordinate]);
+                    neighbour_cell.cost = tentative_cost;
+                    neighbour_cell.f_score = tentative_cost;
+                    open_set.push(neighbour_cell);
+                },
+            }
+        }
+    }
+
+    // Walk back from each of the cheapest exits, trying every predecessor of every cell in turn
+    // path is the route walked back so far, and choices holds, for each cell on it, which of its predecessors to try next
+    // This is a depth first search done with our own stack, since the paths can be far longer than the call stack is deep
+    let mut paths = Vec::new();
+    for exit in best_exits {
+        let mut path = vec![exit];
+        let mut choices = vec![0];
+        while let Some(&current) = path.last() {
+            if current == grid.entrance_location {
+                paths.push(path.iter().rev().copied().collect());
+                if max_paths.is_some_and(|max_paths| paths.len() >= max_paths) {
+                    return paths;
+                }
+                path.pop();
+                choices.pop();
+                continue;
+            }
+            let next_choice = choices.last_mut().unwrap();
+            match predecessors[&current].get(*next_choice) {
+                Some(&predecessor) => {
+                    *next_choice += 1;
+                    path.push(predecessor);
+                    choices.push(0);
+                },
+                // Every way back through this cell has been tried, so step back to the cell after it
+                None => {
+                    path.pop();
+                    choices.pop();
+                },
+            }
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two routes of the same length, one either side of the wall in the middle
+    const PILLAR: &str = "#####\n#---#\n--#--\n#---#\n#####";
+
+    #[test]
+    fn finds_both_routes_round_a_pillar() {
+        let grid: Grid<Cell> = PILLAR.parse().unwrap();
+        let mut paths = solve_all_shortest(&grid, Connectivity::Four, None);
+        paths.sort_by_key(|path| path[2].y);
+        let route = |y| [(0, 2), (1, 2), (1, y), (2, y), (3, y), (3, 2), (4, 2)].map(|(x, y)| Coordinate { x, y }).to_vec();
+        assert_eq!(paths, [route(1), route(3)]);
+        for path in &paths {
+            assert_eq!(grid.validate_path(path, Connectivity::Four), Ok(6));
+        }
+    }
+
+    #[test]
+    fn max_paths_caps_how_many_come_back() {
+        let grid: Grid<Cell> = PILLAR.parse().unwrap();
+        assert_eq!(solve_all_shortest(&grid, Connectivity::Four, Some(1)).len(), 1);
+    }
+
+    #[test]
+    fn a_sealed_exit_has_no_paths() {
+        let grid: Grid<Cell> = "#####\n--#--\n#####".parse().unwrap();
+        assert!(solve_all_shortest(&grid, Connectivity::Four, None).is_empty());
+    }
+}
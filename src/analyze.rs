@@ -0,0 +1,206 @@
+use crate::grid::{Cell, CellType, Coordinate, Grid};
+
+// Facts about a maze that don't need a search to find, for checking a maze over before solving it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MazeAnalysis {
+    pub width: usize,
+    pub height: usize,
+    pub walls: usize,
+    // Every cell that isn't a wall, including the entrance and exits
+    pub open_cells: usize,
+    // Open cells on the border: the gaps in the wall round the maze, whether or not they're its entrance and exits
+    // (an 'S' or 'G' can put those inside, and an open field can have no wall round it at all)
+    pub openings: usize,
+    // Whether the open cells form a tree: all connected, with exactly one route between any two of them
+    pub perfect: bool,
+    pub dead_ends: usize,
+    pub junctions: usize,
+    pub entrance: Coordinate,
+    pub exits: Vec<Coordinate>,
+}
+
+impl Grid<Cell> {
+    // How many of the four cells next to coordinate are open
+    fn open_neighbour_count(&self, coordinate: Coordinate) -> usize {
+        let Coordinate { x, y } = coordinate;
+        // wrapping_sub takes coordinates off the top or left edge to u32::MAX, where try_cell_at finds nothing
+        [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)].into_iter()
+            .filter(|&(x, y)| self.try_cell_at(Coordinate{x, y}).is_some_and(|cell| cell.cell_type != CellType::Wall))
+            .count()
+    }
+
+    // The open cells inside the maze with a given number of ways out; the entrance and exits are left out,
+    // since an opening in the border only ever has one way in
+    fn count_paths_where(&self, ways_out: impl Fn(usize) -> bool) -> usize {
+        self.cells.iter()
+            .filter(|cell| cell.cell_type == CellType::Path && ways_out(self.open_neighbour_count(cell.coordinate)))
+            .count()
+    }
+
+    // How many open cells are the end of a corridor, with only one open cell next to them
+    pub fn dead_ends(&self) -> usize {
+        self.count_paths_where(|ways_out| ways_out == 1)
+    }
+
+    // How many open cells are where corridors meet, with three or four open cells next to them
+    pub fn junctions(&self) -> usize {
+        self.count_paths_where(|ways_out| ways_out >= 3)
+    }
+
+    // Whether the maze is perfect: its open cells form a tree, all connected, with exactly one route between any two of them
+    // Every pair of open cells next to each other (or joined by a portal) is joined with union-find; joining two cells that
+    // are already connected means there's a second route between them, so the maze has a loop. A perfect maze has no loops
+    // and ends up with all its open cells in a single set
+    pub fn is_perfect(&self) -> bool {
+        let index = |coordinate: Coordinate| self.index_of(coordinate);
+        let is_open = |coordinate: Coordinate| self[coordinate].cell_type != CellType::Wall;
+        let mut sets = DisjointSets::new(self.cells.len());
+        let mut joins = 0;
+        for cell in self.cells.iter().filter(|cell| cell.cell_type != CellType::Wall) {
+            let Coordinate { x, y } = cell.coordinate;
+            // Only look right and down, so each pair of neighbours is joined once
+            let mut linked = Vec::new();
+            if (x as usize) + 1 < self.width {
+                linked.push(Coordinate{x: x + 1, y});
+            }
+            if (y as usize) + 1 < self.height {
+                linked.push(Coordinate{x, y: y + 1});
+            }
+            // Likewise each portal only from the end that comes first in the grid
+            if let Some(&partner) = self.portals.get(&cell.coordinate).filter(|&&partner| index(partner) > index(cell.coordinate)) {
+                linked.push(partner);
+            }
+            for neighbour in linked.into_iter().filter(|&neighbour| is_open(neighbour)) {
+                if !sets.union(index(cell.coordinate), index(neighbour)) {
+                    return false;
+                }
+                joins += 1;
+            }
+        }
+        // Every join without a loop merges two sets, so the open cells are all in one set once there's been one fewer join than cells
+        let open_cells = self.cells.iter().filter(|cell| cell.cell_type != CellType::Wall).count();
+        joins + 1 == open_cells
+    }
+}
+
+// Union-find over the cells of the grid, indexed the same way as grid.cells
+// Each set is a group of open cells known to be connected to each other
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+impl DisjointSets {
+    fn new(size: usize) -> DisjointSets {
+        DisjointSets { parents: (0..size).collect() }
+    }
+
+    // The representative of the set containing index, flattening the route to it as we go so later finds are quicker
+    fn find(&mut self, index: usize) -> usize {
+        let mut root = index;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut current = index;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    // Join the sets containing two cells, returning false if they were already in the same set
+    fn union(&mut self, first: usize, second: usize) -> bool {
+        let (first_root, second_root) = (self.find(first), self.find(second));
+        if first_root == second_root {
+            return false;
+        }
+        self.parents[first_root] = second_root;
+        true
+    }
+}
+
+// Count up the cells of the maze and work out whether it's a perfect maze (see Grid::is_perfect)
+pub fn analyze(grid: &Grid<Cell>) -> MazeAnalysis {
+    let walls = grid.cells.iter().filter(|cell| cell.cell_type == CellType::Wall).count();
+    let open_cells = grid.cells.len() - walls;
+    MazeAnalysis {
+        width: grid.width,
+        height: grid.height,
+        walls,
+        open_cells,
+        openings: grid.cells.iter().filter(|cell| cell.cell_type != CellType::Wall && grid.on_border(cell.coordinate)).count(),
+        perfect: grid.is_perfect(),
+        dead_ends: grid.dead_ends(),
+        junctions: grid.junctions(),
+        entrance: grid.entrance_location,
+        exits: grid.exits.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitgrid::BitGrid, generate::generate_text, parse::GridConfig};
+
+    #[test]
+    fn openings_count_the_gaps_in_the_border() {
+        let grid: Grid<Cell> = "#####\n-S--#\n#-#-#\n#--G-\n#####".parse().unwrap();
+        let analysis = analyze(&grid);
+        // The markers are inside, so neither border gap is the entrance or the exit
+        assert_eq!((analysis.entrance, analysis.exits), (Coordinate { x: 1, y: 1 }, vec![Coordinate { x: 3, y: 3 }]));
+        assert_eq!(analysis.openings, 2);
+    }
+
+    #[test]
+    fn openings_count_every_open_border_cell_of_an_open_field() {
+        let config = GridConfig { openings: Some((Coordinate { x: 0, y: 0 }, Coordinate { x: 3, y: 2 })), ..GridConfig::default() };
+        let maze = "----\n-#--\n----";
+        let grid = Grid::from_string_with_config(maze, &config).unwrap();
+        assert_eq!(analyze(&grid).openings, 10);
+        let bit_grid = BitGrid::from_reader_with_config(maze.as_bytes(), &config).unwrap();
+        assert_eq!(bit_grid.analyze(), analyze(&grid));
+    }
+
+    // A corridor with three dead-end teeth hanging off it: each tooth is a dead end, and where it joins is a junction
+    #[test]
+    fn counts_the_dead_ends_and_junctions_of_a_comb() {
+        let grid: Grid<Cell> = "#######\n-------\n#-#-#-#\n#######".parse().unwrap();
+        assert_eq!((grid.dead_ends(), grid.junctions()), (3, 3));
+        let analysis = analyze(&grid);
+        assert_eq!((analysis.dead_ends, analysis.junctions), (3, 3));
+    }
+
+    // The middle of a crossroads has four ways out, and the entrance and exit aren't counted as dead ends
+    #[test]
+    fn a_crossroads_is_one_junction() {
+        let grid: Grid<Cell> = "#-#\n---\n#-#".parse().unwrap();
+        assert_eq!((grid.dead_ends(), grid.junctions()), (0, 1));
+        let grid: Grid<Cell> = "#####\n-----\n#####".parse().unwrap();
+        assert_eq!((grid.dead_ends(), grid.junctions()), (0, 0));
+    }
+
+    // The comb is a tree; knocking out the walls between its teeth joins them up into loops
+    #[test]
+    fn a_maze_with_a_loop_isnt_perfect() {
+        let comb: Grid<Cell> = "#######\n-------\n#-#-#-#\n#######".parse().unwrap();
+        assert!(comb.is_perfect());
+        let looped: Grid<Cell> = "#######\n-------\n#-----#\n#######".parse().unwrap();
+        assert!(!looped.is_perfect());
+        assert!(!analyze(&looped).perfect);
+    }
+
+    // A corridor with a pocket walled off from it has no loops, but isn't connected either
+    #[test]
+    fn a_walled_off_pocket_isnt_perfect() {
+        let grid: Grid<Cell> = "#####\n-----\n#####\n##-##\n#####".parse().unwrap();
+        assert!(!grid.is_perfect());
+    }
+
+    #[test]
+    fn generated_mazes_are_perfect() {
+        for seed in 0..5 {
+            let grid: Grid<Cell> = generate_text(21, 11, seed).parse().unwrap();
+            assert!(grid.is_perfect(), "seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::{collections::{BinaryHeap, HashMap}, time::Instant};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, solve::{Connectivity, Heuristic, SearchStats, SearchStep, Solution}};
+
+// One half of a bidirectional search, with its own open set, best costs and parents
+// The forward half starts at the entrance and aims for the exits; the backward half starts at the exits and aims for the entrance
+struct SearchFront {
+    open_set: BinaryHeap<Cell>,
+    best_costs: HashMap<Coordinate, usize>,
+    parents: HashMap<Coordinate, Coordinate>,
+    // Where this half is heading, for its heuristic
+    targets: Vec<Coordinate>,
+    forward: bool,
+}
+impl SearchFront {
+    fn new(grid: &Grid<Cell>, starts: &[Coordinate], targets: Vec<Coordinate>, forward: bool) -> SearchFront {
+        let mut front = SearchFront {
+            open_set: BinaryHeap::new(),
+            best_costs: HashMap::new(),
+            parents: HashMap::new(),
+            targets,
+            forward,
+        };
+        // Seed with fresh copies of the starting cells, as the grid's own may still hold costs from an earlier search
+        for &start in starts {
+            front.best_costs.insert(start, 0);
+            front.open_set.push(Cell { cost: 0, f_score: 0, ..grid[start] });
+        }
+        front
+    }
+
+    // The lowest f-score waiting in the open set, throwing away any stale copies sitting on top of it
+    fn min_f_score(&mut self) -> Option<usize> {
+        while let Some(top) = self.open_set.peek() {
+            if top.cost > self.best_costs[&top.coordinate] {
+                self.open_set.pop();
+                continue;
+            }
+            return Some(top.f_score);
+        }
+        None
+    }
+}
+
+// Expand the best cell in front, recording any cell it reaches that other has already reached as a possible meeting point
+// Returns the coordinate expanded, if there was one
+fn expand(grid: &Grid<Cell>, front: &mut SearchFront, other: &SearchFront, connectivity: Connectivity, heuristic: Heuristic,
+          best_meeting: &mut Option<(usize, Coordinate)>) -> Option<Coordinate> {
+    let current_cell = front.open_set.pop()?;
+    // The backward half goes against the way steps are taken, so through one-way cells it has to look at where steps come from
+    let neighbours = if front.forward { grid.neighbours(current_cell.coordinate, connectivity) } else { grid.predecessors(current_cell.coordinate, connectivity) };
+    for neighbour in neighbours {
+        let mut neighbour_cell = grid[neighbour];
+        // Walls are impassable; each half's starting cells cost 0, so the best cost check below stops it stepping back onto them
+        if neighbour_cell.cell_type == CellType::Wall {
+            continue;
+        }
+        // Steps are costed by the weight of the cell being stepped into on the way from entrance to exit,
+        // so the backward half pays for the cell it's leaving rather than the one it's moving to
+        let weight = if front.forward { neighbour_cell.weight } else { current_cell.weight };
+        let tentative_cost = current_cell.cost + connectivity.step_cost_between(grid, current_cell.coordinate, neighbour) * weight;
+        if front.best_costs.get(&neighbour).is_some_and(|&best_cost| tentative_cost >= best_cost) {
+            continue;
+        }
+        front.best_costs.insert(neighbour, tentative_cost);
+        front.parents.insert(neighbour, current_cell.coordinate);
+        neighbour_cell.cost = tentative_cost;
+        neighbour_cell.h = heuristic.estimate_nearest(grid, neighbour, &front.targets, connectivity);
+        neighbour_cell.f_score = neighbour_cell.cost + neighbour_cell.h;
+        front.open_set.push(neighbour_cell);
+        // If the other half has already reached this cell, there's a complete route through it
+        if let Some(&other_cost) = other.best_costs.get(&neighbour) {
+            let route_cost = tentative_cost + other_cost;
+            if best_meeting.is_none_or(|(best_route_cost, _)| route_cost < best_route_cost) {
+                *best_meeting = Some((route_cost, neighbour));
+            }
+        }
+    }
+    Some(current_cell.coordinate)
+}
+
+// A* from both ends at once: one search forwards from the entrance and one backwards from the exits, until they meet
+// Each half only has to explore about as far as the meeting point, which on big open mazes is far fewer cells than one
+// search going the whole way. Finding a meeting point isn't enough to stop, since a cheaper route might still meet elsewhere;
+// we stop once the cheapest f-score left in either open set is at least the cost of the best route found, because every
+// route not yet found must cost at least that much
+// If on_step is given, it's called after every cell either half expands, with both halves' open sets as the frontier
+pub fn solve_bidirectional(grid: &Grid<Cell>, connectivity: Connectivity, heuristic: Heuristic,
+                           mut on_step: Option<&mut dyn FnMut(&SearchStep)>) -> (Option<Solution>, SearchStats) {
+    let start_time = Instant::now();
+    let mut stats = SearchStats::default();
+    let mut forward = SearchFront::new(grid, &[grid.entrance_location], grid.exits.clone(), true);
+    let mut backward = SearchFront::new(grid, &grid.exits, vec![grid.entrance_location], false);
+    // The cheapest complete route found so far, as its total cost and the cell where the two halves met
+    let mut best_meeting: Option<(usize, Coordinate)> = None;
+
+    // If either half runs out of cells, nothing better than the best meeting so far can be found
+    while let (Some(forward_min), Some(backward_min)) = (forward.min_f_score(), backward.min_f_score()) {
+        if best_meeting.is_some_and(|(route_cost, _)| forward_min >= route_cost || backward_min >= route_cost) {
+            break;
+        }
+        // Expand whichever half has the smaller frontier, which keeps the two roughly balanced
+        let expanded = if forward.open_set.len() <= backward.open_set.len() {
+            expand(grid, &mut forward, &backward, connectivity, heuristic, &mut best_meeting)
+        }
+        else {
+            expand(grid, &mut backward, &forward, connectivity, heuristic, &mut best_meeting)
+        };
+        stats.nodes_expanded += 1;
+        stats.max_frontier_size = stats.max_frontier_size.max(forward.open_set.len() + backward.open_set.len());
+        if let (Some(on_step), Some(expanded)) = (on_step.as_mut(), expanded) {
+            let frontier = forward.open_set.iter().chain(backward.open_set.iter()).map(|cell| cell.coordinate).collect();
+            on_step(&SearchStep { expanded, frontier });
+        }
+    }
+
+    let Some((cost, meeting_point)) = best_meeting else {
+        stats.elapsed = start_time.elapsed();
+        return (None, stats);
+    };
+    // Follow the forward parents back to the entrance, then the backward parents on to the exit
+    let mut path = vec![meeting_point];
+    while let Some(&parent) = forward.parents.get(path.last().unwrap()) {
+        path.push(parent);
+    }
+    path.reverse();
+    while let Some(&parent) = backward.parents.get(path.last().unwrap()) {
+        path.push(parent);
+    }
+    let exit = *path.last().unwrap();
+    stats.path_length = path.len();
+    stats.elapsed = start_time.elapsed();
+    (Some(Solution { path, exit, cost }), stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_maze, solve::{solve, solve_with, Algorithm, SolveOutcome}};
+
+    // The grid's cells still hold the first search's costs when the second one starts
+    #[test]
+    fn solves_a_grid_already_searched() {
+        let mut grid = load_maze("maze-Easy");
+        let SolveOutcome::Found { cost, .. } = solve(&mut grid) else {
+            panic!("maze-Easy has no solution");
+        };
+        let (solution, _) = solve_bidirectional(&grid, Connectivity::Four, Heuristic::Manhattan, None);
+        assert_eq!(solution.map(|solution| solution.cost), Some(cost));
+    }
+
+    #[test]
+    fn matches_astar_on_a_fresh_grid() {
+        let grid = load_maze("maze-Medium");
+        let (solution, _) = solve_bidirectional(&grid, Connectivity::Four, Heuristic::Manhattan, None);
+        let solution = solution.unwrap();
+        assert_eq!(solution.cost, 320);
+        assert_eq!(grid.validate_path(&solution.path, Connectivity::Four), Ok(320));
+    }
+
+    // On maze-Large the two halves meet having looked at fewer cells between them than A* does on its own
+    // (that's not so on every maze: where the exit's end has more side passages than the entrance's, it's the other way round)
+    #[test]
+    fn expands_fewer_cells_than_astar_on_maze_large() {
+        let grid = load_maze("maze-Large");
+        let (bidirectional, bidirectional_stats) = solve_bidirectional(&grid, Connectivity::Four, Heuristic::Manhattan, None);
+        let (astar, astar_stats) = solve_with(&mut grid.clone(), Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, None);
+        assert_eq!(bidirectional.unwrap().cost, astar.unwrap().cost);
+        assert!(bidirectional_stats.nodes_expanded < astar_stats.nodes_expanded,
+                "bidirectional search expanded {} cells and A* {}", bidirectional_stats.nodes_expanded, astar_stats.nodes_expanded);
+    }
+}
@@ -0,0 +1,157 @@
+use std::io::{self, Read, Write};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid, NeighbourOrder}, parse::MazeError};
+
+// A compact binary form of a maze, for ones big enough that the text takes a while to read
+// Everything is little-endian:
+//   the 4 bytes "MAZB", then a version byte (1)
+//   width and height, a u32 each
+//   the entrance's x and y, a u32 each
+//   how many exits there are as a u32, then each one's x and y
+//   one bit a cell, row by row, set for walls: bit i of byte j is cell 8j + i, with the last byte padded out with clear bits
+// That's an eighth of a byte a cell, against the two bytes a cell of the text format
+const MAGIC: &[u8; 4] = b"MAZB";
+const VERSION: u8 = 1;
+
+impl Grid<Cell> {
+    // Write the maze in the binary format above
+    // There's nowhere in it for weighted terrain, portals or one-way cells, so a maze with any of them is an InvalidInput
+    // error rather than being written without them. Only the maze itself is written: the search state, step costs, wrapping
+    // and neighbour order are all left behind, and from_binary gives back their defaults
+    pub fn to_binary<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        if !self.portals.is_empty() || self.has_one_way_cells() || self.cells.iter().any(|cell| cell.weight > 1) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "the binary format can't hold weighted terrain, portals or one-way cells"));
+        }
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        // Both sides are at most MAX_SIDE, so they fit in a u32
+        for value in [self.width as u32, self.height as u32] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        write_coordinate(&mut writer, self.entrance_location)?;
+        writer.write_all(&(self.exits.len() as u32).to_le_bytes())?;
+        for &exit in &self.exits {
+            write_coordinate(&mut writer, exit)?;
+        }
+        let mut walls = vec![0u8; self.cells.len().div_ceil(8)];
+        for (index, cell) in self.cells.iter().enumerate() {
+            if cell.cell_type == CellType::Wall {
+                walls[index / 8] |= 1 << (index % 8);
+            }
+        }
+        writer.write_all(&walls)
+    }
+
+    // Read a maze written by to_binary, checking it the way the text parser would: the entrance and exits have to be open
+    // cells inside the maze, there has to be at least one exit, and the entrance can't be one of them
+    pub fn from_binary<R: Read>(mut reader: R) -> Result<Grid<Cell>, MazeError> {
+        let mut header = [0; 5];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(MazeError::Malformed("not a binary maze (it doesn't start with \"MAZB\")".to_string()));
+        }
+        if header[4] != VERSION {
+            return Err(MazeError::Malformed(format!("binary maze is version {}, but only version {} can be read", header[4], VERSION)));
+        }
+        let width = read_u32(&mut reader)? as usize;
+        let height = read_u32(&mut reader)? as usize;
+        if width == 0 || height == 0 {
+            return Err(MazeError::EmptyFile);
+        }
+        let entrance_location = read_coordinate(&mut reader)?;
+        let exit_count = read_u32(&mut reader)?;
+        let exits = (0..exit_count).map(|_| read_coordinate(&mut reader)).collect::<Result<Vec<_>, _>>()?;
+        // Read the walls through take rather than into a buffer sized from the header, so a corrupt width and height
+        // can't have us allocate far more than the file holds
+        let cell_count = width.checked_mul(height).ok_or(MazeError::TooLarge { width, height })?;
+        let mut walls = Vec::new();
+        reader.take(cell_count.div_ceil(8) as u64).read_to_end(&mut walls)?;
+        if walls.len() != cell_count.div_ceil(8) {
+            return Err(MazeError::Malformed(format!("binary maze ends after {} of its {} bytes of walls", walls.len(), cell_count.div_ceil(8))));
+        }
+
+        let is_wall = |index: usize| walls[index / 8] & (1 << (index % 8)) != 0;
+        for &coordinate in exits.iter().chain([&entrance_location]) {
+            if coordinate.x as usize >= width || coordinate.y as usize >= height {
+                return Err(MazeError::OutOfBounds(coordinate));
+            }
+            if is_wall(coordinate.y as usize * width + coordinate.x as usize) {
+                return Err(MazeError::InWall(coordinate));
+            }
+        }
+        if exits.is_empty() {
+            return Err(MazeError::MissingExit);
+        }
+        if exits.contains(&entrance_location) {
+            return Err(MazeError::EntranceIsExit(entrance_location));
+        }
+        let cells = (0..cell_count).map(|index| {
+            let coordinate = Coordinate::new(index % width, index / width);
+            let cell_type = if is_wall(index) {
+                CellType::Wall
+            }
+            else if coordinate == entrance_location {
+                CellType::Entrance
+            }
+            else if exits.contains(&coordinate) {
+                CellType::Exit
+            }
+            else {
+                CellType::Path
+            };
+            Cell::new(coordinate, cell_type)
+        }).collect();
+        Ok(Grid {
+            width,
+            height,
+            entrance_location,
+            exits,
+            portals: Default::default(),
+            cells,
+            cost_x: 1,
+            cost_y: 1,
+            toroidal: false,
+            neighbour_order: NeighbourOrder::default(),
+        })
+    }
+}
+
+fn write_coordinate<W: Write>(writer: &mut W, coordinate: Coordinate) -> io::Result<()> {
+    writer.write_all(&coordinate.x.to_le_bytes())?;
+    writer.write_all(&coordinate.y.to_le_bytes())
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_coordinate<R: Read>(reader: &mut R) -> io::Result<Coordinate> {
+    Ok(Coordinate { x: read_u32(reader)?, y: read_u32(reader)? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_maze;
+
+    #[test]
+    fn a_text_maze_round_trips_through_binary() {
+        for name in ["maze-Easy", "maze-Medium"] {
+            let grid = load_maze(name);
+            let mut binary = Vec::new();
+            grid.to_binary(&mut binary).unwrap();
+            assert_eq!(Grid::from_binary(&binary[..]).unwrap(), grid, "{}", name);
+            // 33 bytes of header for one exit, then a bit a cell
+            assert_eq!(binary.len(), 33 + grid.cells.len().div_ceil(8), "{}", name);
+        }
+    }
+
+    #[test]
+    fn mazes_it_cant_hold_are_rejected() {
+        let weighted: Grid<Cell> = "#####\n--5--\n#####".parse().unwrap();
+        assert_eq!(weighted.to_binary(Vec::new()).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert!(matches!(Grid::from_binary(&b"MAZE\x01"[..]), Err(MazeError::Malformed(_))));
+    }
+}
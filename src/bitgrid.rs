@@ -0,0 +1,314 @@
+use std::{io::BufRead, time::Instant};
+
+use crate::{analyze::MazeAnalysis, grid::{CellType, Coordinate, MAX_SIDE}, parse::{decode, decompressed, GridConfig, MazeError}, solve::SearchStats};
+
+// A maze stored as one bit per cell, set for open cells and clear for walls, for mazes too big to hold as a Grid
+// A Grid keeps a whole Cell for every cell (tens of bytes each, with its search bookkeeping), so a maze of a billion cells
+// needs tens of gigabytes; as bits it's 125MB. The price is everything but walls and paths: there are no weights, portals or
+// search state, so it can only be analysed or have its shortest path counted, four ways at one cost a step
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    // The cells row by row, 64 to a word, bit i of word j being cell 64j + i
+    open: Vec<u64>,
+    entrance: Coordinate,
+    exits: Vec<Coordinate>,
+}
+impl BitGrid {
+    // Read a maze in the usual text format a line at a time, so only the bits (and the line being read) are ever held
+    // It reads the same as Grid::from_reader_with_config, openings and 'S' and 'G' markers included, except that weighted
+    // terrain, portals and one-way cells are errors, as there's nowhere to keep them
+    pub fn from_reader_with_config<R: BufRead>(reader: R, config: &GridConfig) -> Result<BitGrid, MazeError> {
+        let mut reader = decompressed(reader)?;
+        let mut grid = BitGrid { width: 0, height: 0, open: Vec::new(), entrance: Coordinate{x: 0, y: 0}, exits: Vec::new() };
+        let mut line = Vec::new();
+        // Blank lines only count as rows (which are then too short) if there's a row after them
+        let mut blank_lines = 0;
+        // Openings are only openings on the border, and we can't tell the last row is the bottom of the maze until the file
+        // ends, so the opening characters in each row are held back until we know which row comes next
+        let mut openings = Vec::new();
+        let mut last_row_openings = Vec::new();
+        let (mut starts, mut goals) = (Vec::new(), Vec::new());
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            let text = decode(std::mem::take(&mut line));
+            if config.comment_prefix.as_ref().is_some_and(|prefix| text.starts_with(prefix.as_str())) {
+                continue;
+            }
+            let row: Vec<(char, CellType)> = text.chars().filter_map(|char| config.classify(char).map(|cell_type| (char, cell_type))).collect();
+            if row.is_empty() {
+                blank_lines += usize::from(grid.height > 0);
+                continue;
+            }
+            if grid.height == 0 {
+                grid.width = row.len();
+            }
+            if grid.width > MAX_SIDE || grid.height == MAX_SIDE {
+                return Err(MazeError::TooLarge { width: grid.width, height: grid.height + 1 });
+            }
+            if blank_lines > 0 || row.len() != grid.width {
+                let found = if blank_lines > 0 { 0 } else { row.len() };
+                return Err(MazeError::RaggedRows { row: grid.height, expected: grid.width, found });
+            }
+            // The row before this one wasn't the last after all, so only the ends of it are on the border (unless it was the first)
+            openings.extend(last_row_openings.drain(..).filter(|&Coordinate { x, y }| y == 0 || x == 0 || x as usize == grid.width - 1));
+            let y = grid.height;
+            for (x, (char, cell_type)) in row.into_iter().enumerate() {
+                let coordinate = Coordinate::new(x, y);
+                match cell_type {
+                    CellType::Wall => continue,
+                    CellType::Entrance | CellType::Exit => last_row_openings.push(coordinate),
+                    _ => match char {
+                        _ if config.is_configured(char) => (),
+                        'S' => starts.push(coordinate),
+                        'G' => goals.push(coordinate),
+                        '2'..='9' | 'A'..='Z' | '^' | 'v' | '<' | '>' => return Err(MazeError::Malformed(format!(
+                            "{} has '{}', but a BitGrid can't hold weighted terrain, portals or one-way cells", coordinate, char))),
+                        _ => (),
+                    },
+                }
+                grid.set_open(coordinate);
+            }
+            grid.height += 1;
+        }
+        if grid.height == 0 {
+            return Err(MazeError::EmptyFile);
+        }
+        openings.extend(last_row_openings);
+
+        // Pick the entrance and exits just as Grid does
+        let marker = |marker: char, coordinates: Vec<Coordinate>| match coordinates[..] {
+            [] => Ok(None),
+            [coordinate] => Ok(Some(coordinate)),
+            _ => Err(MazeError::DuplicateMarker { marker, count: coordinates.len() }),
+        };
+        let (start, goal) = (marker('S', starts)?, marker('G', goals)?);
+        let (start, goal) = match config.openings {
+            Some((entrance, exit)) => {
+                for coordinate in [entrance, exit] {
+                    if coordinate.x as usize >= grid.width || coordinate.y as usize >= grid.height {
+                        return Err(MazeError::OutOfBounds(coordinate));
+                    }
+                    if !grid.is_open(coordinate) {
+                        return Err(MazeError::InWall(coordinate));
+                    }
+                }
+                if entrance == exit {
+                    return Err(MazeError::EntranceIsExit(entrance));
+                }
+                (Some(entrance), Some(exit))
+            },
+            None => (start, goal),
+        };
+        let mut openings = openings.into_iter();
+        grid.entrance = match start {
+            Some(start) => start,
+            None => openings.next().ok_or(MazeError::MissingEntrance)?,
+        };
+        grid.exits = match goal {
+            Some(goal) => vec![goal],
+            None => openings.collect(),
+        };
+        if grid.exits.is_empty() {
+            return Err(MazeError::MissingExit);
+        }
+        Ok(grid)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn entrance(&self) -> Coordinate {
+        self.entrance
+    }
+
+    pub fn exits(&self) -> &[Coordinate] {
+        &self.exits
+    }
+
+    // Whether coordinate is inside the maze and not a wall
+    pub fn is_open(&self, coordinate: Coordinate) -> bool {
+        (coordinate.x as usize) < self.width && (coordinate.y as usize) < self.height && get_bit(&self.open, self.index_of(coordinate))
+    }
+
+    fn index_of(&self, coordinate: Coordinate) -> usize {
+        coordinate.y as usize * self.width + coordinate.x as usize
+    }
+
+    fn set_open(&mut self, coordinate: Coordinate) {
+        let index = self.index_of(coordinate);
+        set_bit(&mut self.open, index);
+    }
+
+    // The open cells next to coordinate, four ways
+    fn open_neighbours(&self, coordinate: Coordinate) -> impl Iterator<Item = Coordinate> + '_ {
+        let Coordinate { x, y } = coordinate;
+        // wrapping_sub takes coordinates off the top or left edge to u32::MAX, which is_open knows is outside
+        [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)].into_iter()
+            .map(|(x, y)| Coordinate{x, y})
+            .filter(|&neighbour| self.is_open(neighbour))
+    }
+
+    // The same facts about the maze as analyze gives for a Grid
+    // Whether it's perfect is worked out without union-find (which would need a word per cell): the open cells form a tree
+    // if there's one fewer pair of neighbouring open cells than there are open cells, and all of them can be reached
+    pub fn analyze(&self) -> MazeAnalysis {
+        let (mut open_cells, mut openings, mut joins, mut dead_ends, mut junctions) = (0, 0, 0, 0, 0);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let coordinate = Coordinate::new(x, y);
+                if !self.is_open(coordinate) {
+                    continue;
+                }
+                open_cells += 1;
+                if x == 0 || y == 0 || x == self.width - 1 || y == self.height - 1 {
+                    openings += 1;
+                }
+                // Only count the pairs to the right and below, so each is counted once
+                joins += [Coordinate::new(x + 1, y), Coordinate::new(x, y + 1)].into_iter().filter(|&neighbour| self.is_open(neighbour)).count();
+                if coordinate == self.entrance || self.exits.contains(&coordinate) {
+                    continue;
+                }
+                match self.open_neighbours(coordinate).count() {
+                    1 => dead_ends += 1,
+                    ways_out if ways_out >= 3 => junctions += 1,
+                    _ => (),
+                }
+            }
+        }
+        let reached = self.reachable_count(self.entrance);
+        MazeAnalysis {
+            width: self.width,
+            height: self.height,
+            walls: self.width * self.height - open_cells,
+            open_cells,
+            openings,
+            perfect: joins + 1 == open_cells && reached == open_cells,
+            dead_ends,
+            junctions,
+            entrance: self.entrance,
+            exits: self.exits.clone(),
+        }
+    }
+
+    // Count the steps on the shortest path from the entrance to the nearest exit, without ever finding the path itself
+    // A breadth-first search a ring at a time, keeping only the cells seen (as bits) and the current and next rings
+    // Returns None if no exit can be reached; stats.path_length is the number of cells on the path, one more than the steps
+    pub fn shortest_path_steps(&self) -> (Option<usize>, SearchStats) {
+        let start_time = Instant::now();
+        let mut stats = SearchStats::default();
+        let mut seen = self.no_cells();
+        set_bit(&mut seen, self.index_of(self.entrance));
+        let mut ring = vec![self.entrance];
+        let mut steps = 0;
+        while !ring.is_empty() {
+            stats.max_frontier_size = stats.max_frontier_size.max(ring.len());
+            if ring.iter().any(|coordinate| self.exits.contains(coordinate)) {
+                stats.path_length = steps + 1;
+                stats.elapsed = start_time.elapsed();
+                return (Some(steps), stats);
+            }
+            let mut next_ring = Vec::new();
+            for coordinate in ring {
+                stats.nodes_expanded += 1;
+                for neighbour in self.open_neighbours(coordinate) {
+                    let index = self.index_of(neighbour);
+                    if !get_bit(&seen, index) {
+                        set_bit(&mut seen, index);
+                        next_ring.push(neighbour);
+                    }
+                }
+            }
+            ring = next_ring;
+            steps += 1;
+        }
+        stats.elapsed = start_time.elapsed();
+        (None, stats)
+    }
+
+    // How many open cells can be reached from start, start included
+    fn reachable_count(&self, start: Coordinate) -> usize {
+        let mut seen = self.no_cells();
+        set_bit(&mut seen, self.index_of(start));
+        let mut stack = vec![start];
+        let mut reached = 1;
+        while let Some(coordinate) = stack.pop() {
+            for neighbour in self.open_neighbours(coordinate) {
+                let index = self.index_of(neighbour);
+                if !get_bit(&seen, index) {
+                    set_bit(&mut seen, index);
+                    reached += 1;
+                    stack.push(neighbour);
+                }
+            }
+        }
+        reached
+    }
+
+    // A bit for every cell, all clear
+    fn no_cells(&self) -> Vec<u64> {
+        vec![0; (self.width * self.height).div_ceil(64)]
+    }
+}
+
+fn get_bit(bits: &[u64], index: usize) -> bool {
+    bits.get(index / 64).is_some_and(|word| word & (1 << (index % 64)) != 0)
+}
+
+// Set a bit, growing bits to hold it if need be
+fn set_bit(bits: &mut Vec<u64>, index: usize) {
+    if index / 64 >= bits.len() {
+        bits.resize(index / 64 + 1, 0);
+    }
+    bits[index / 64] |= 1 << (index % 64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analyze::analyze, grid::{Cell, Grid}, load_maze, solve::{solve, SolveOutcome}};
+
+    fn read(name: &str) -> BitGrid {
+        let path = format!("{}/mazes/{}.txt", env!("CARGO_MANIFEST_DIR"), name);
+        BitGrid::from_reader_with_config(std::io::BufReader::new(std::fs::File::open(path).unwrap()), &GridConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn reads_and_solves_the_same_as_a_grid() {
+        for name in ["maze-Easy", "maze-Medium", "maze-Large"] {
+            let (bit_grid, mut grid) = (read(name), load_maze(name));
+            assert_eq!(bit_grid.analyze(), analyze(&grid), "{}", name);
+            assert!(grid.cells.iter().all(|cell| bit_grid.is_open(cell.coordinate) == (cell.cell_type != CellType::Wall)), "{}", name);
+            let SolveOutcome::Found { cost, .. } = solve(&mut grid) else {
+                panic!("{} has no solution", name);
+            };
+            assert_eq!(bit_grid.shortest_path_steps().0, Some(cost), "{}", name);
+        }
+    }
+
+    // maze-VLarge2 is a little over a million cells: about 130KB as bits, against tens of megabytes as Cells
+    #[test]
+    fn bits_take_a_fraction_of_the_memory_of_cells() {
+        let bit_grid = read("maze-VLarge2");
+        let cells = bit_grid.width() * bit_grid.height();
+        let bit_bytes = bit_grid.open.len() * size_of::<u64>();
+        assert!(bit_bytes <= cells.div_ceil(64) * size_of::<u64>());
+        assert!(bit_bytes * 100 < cells * size_of::<Cell>(), "{} bytes of bits for {} cells", bit_bytes, cells);
+    }
+
+    // A Grid reads the same text fine, weighting the middle cell
+    #[test]
+    fn terrain_has_nowhere_to_go() {
+        assert!(BitGrid::from_reader_with_config(&b"#####\n--5--\n#####"[..], &GridConfig::default()).is_err());
+        let _: Grid<Cell> = "#####\n--5--\n#####".parse().unwrap();
+    }
+}
@@ -0,0 +1,126 @@
+use std::{cmp::Reverse, collections::{BinaryHeap, HashMap}, time::Instant};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, solve::{Connectivity, SearchError, SearchLimits, SearchStats, Solution}};
+
+// Where the search has got to: a cell, and the cell the route came into it from (None for the entrance)
+// Costs can depend on the step before, so reaching a cell from one side isn't the same as reaching it from another
+type State = (Coordinate, Option<Coordinate>);
+
+// A* with the cost model supplied by the caller, for trying out costs and heuristics the crate doesn't have
+// move_cost(previous, from, to) is what stepping from one cell to a neighbour costs, where previous is the cell the route
+// came into from from (None when from is the entrance), so a cost can look at the way the route was already going; it's
+// only asked about steps onto open cells, and the grid's own step costs and terrain weights are ignored in its favour.
+// heuristic(from, exit) estimates the cost from a cell to an exit, and the search uses the lowest estimate over every exit
+// The heuristic has to be admissible, never more than the true cheapest cost from the cell to the exit under move_cost,
+// or the path found may not be the cheapest; one that's 0 everywhere always is, and makes this Dijkstra's algorithm
+// As the cost of a step can depend on the one before, the search is over (cell, previous cell) pairs, as many as five (or
+// nine, moving eight ways) times as many states as cells; each is only reached by its cheapest route, so a cost penalising
+// turns still finds the cheapest path counting them
+// unit_cost and manhattan are the plain choices, for replacing just one of the two
+pub fn solve_with_costs(grid: &Grid<Cell>, connectivity: Connectivity, limits: SearchLimits,
+                        move_cost: impl Fn(Option<Coordinate>, Coordinate, Coordinate) -> usize,
+                        heuristic: impl Fn(Coordinate, Coordinate) -> usize)
+                        -> Result<(Option<Solution>, SearchStats), SearchError> {
+    let start_time = Instant::now();
+    let mut stats = SearchStats::default();
+    let start: State = (grid.entrance_location, None);
+    let estimate = |coordinate: Coordinate| grid.exits.iter().map(|&exit| heuristic(coordinate, exit)).min().unwrap_or_default();
+    // Ordered the same way as Cells: lowest f-score first, ties going to the lowest heuristic and then to the top left
+    // The previous cell goes in as (y, x) too, as Coordinates can't be ordered
+    let h = estimate(start.0);
+    let mut open_set = BinaryHeap::from([Reverse((h, h, start.0.y, start.0.x, None::<(u32, u32)>))]);
+    let mut best_costs = HashMap::from([(start, 0)]);
+    let mut parents: HashMap<State, State> = HashMap::new();
+    let mut exit_reached = None;
+    while let Some(Reverse((f_score, h, y, x, previous))) = open_set.pop() {
+        let state = (Coordinate{x, y}, previous.map(|(y, x)| Coordinate{x, y}));
+        let cost = best_costs[&state];
+        // A stale copy left behind when a cheaper route to this state was found
+        if f_score > cost + h {
+            continue;
+        }
+        let current = state.0;
+        if grid.exits.contains(&current) {
+            exit_reached = Some(state);
+            break;
+        }
+        limits.check(&stats, start_time)?;
+        stats.nodes_expanded += 1;
+        for neighbour in grid.neighbours(current, connectivity) {
+            if grid[neighbour].cell_type == CellType::Wall || neighbour == start.0 {
+                continue;
+            }
+            let tentative_cost = cost + move_cost(state.1, current, neighbour);
+            let next = (neighbour, Some(current));
+            if best_costs.get(&next).is_some_and(|&best_cost| tentative_cost >= best_cost) {
+                continue;
+            }
+            best_costs.insert(next, tentative_cost);
+            parents.insert(next, state);
+            let h = estimate(neighbour);
+            open_set.push(Reverse((tentative_cost + h, h, neighbour.y, neighbour.x, Some((current.y, current.x)))));
+        }
+        stats.max_frontier_size = stats.max_frontier_size.max(open_set.len());
+    }
+    let Some(exit_state) = exit_reached else {
+        stats.elapsed = start_time.elapsed();
+        return Ok((None, stats));
+    };
+    let mut path = vec![exit_state.0];
+    let mut state = exit_state;
+    while let Some(&parent) = parents.get(&state) {
+        path.push(parent.0);
+        state = parent;
+    }
+    path.reverse();
+    stats.path_length = path.len();
+    stats.elapsed = start_time.elapsed();
+    Ok((Some(Solution { path, exit: exit_state.0, cost: best_costs[&exit_state] }), stats))
+}
+
+// Every step costs 1, wherever it goes
+pub fn unit_cost(_previous: Option<Coordinate>, _from: Coordinate, _to: Coordinate) -> usize {
+    1
+}
+
+// The number of steps between two cells moving four ways, with nothing in the way
+pub fn manhattan(from: Coordinate, to: Coordinate) -> usize {
+    from.x.abs_diff(to.x) as usize + from.y.abs_diff(to.y) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_maze;
+
+    // How many times a path changes direction
+    fn turns(path: &[Coordinate]) -> usize {
+        let step = |from: Coordinate, to: Coordinate| (to.x as i64 - from.x as i64, to.y as i64 - from.y as i64);
+        path.windows(3).filter(|cells| step(cells[0], cells[1]) != step(cells[1], cells[2])).count()
+    }
+
+    #[test]
+    fn unit_costs_and_manhattan_match_astar() {
+        let grid = load_maze("maze-Medium");
+        let (solution, _) = solve_with_costs(&grid, Connectivity::Four, SearchLimits::default(), unit_cost, manhattan).unwrap();
+        assert_eq!(solution.unwrap().cost, 320);
+    }
+
+    // The shortest way through zig-zags; going round the bottom is longer, but turns half as often
+    #[test]
+    fn a_cost_penalising_turns_gives_a_straighter_path() {
+        let grid: Grid<Cell> = "#########\n--#---#--\n#-#-#-#-#\n#---#---#\n#-#####-#\n#-#####-#\n#-------#\n#########".parse().unwrap();
+        let (shortest, _) = solve_with_costs(&grid, Connectivity::Four, SearchLimits::default(), unit_cost, manhattan).unwrap();
+        let shortest = shortest.unwrap();
+        assert_eq!((shortest.cost, turns(&shortest.path)), (16, 8));
+        let turn_cost = |previous: Option<Coordinate>, from: Coordinate, to: Coordinate| {
+            let turned = previous.is_some_and(|previous| turns(&[previous, from, to]) == 1);
+            1 + if turned { 10 } else { 0 }
+        };
+        let (straighter, _) = solve_with_costs(&grid, Connectivity::Four, SearchLimits::default(), turn_cost, manhattan).unwrap();
+        let straighter = straighter.unwrap();
+        assert_eq!((straighter.path.len() - 1, turns(&straighter.path)), (18, 4));
+        assert_eq!(straighter.cost, 18 + 4 * 10);
+        assert_eq!(grid.validate_path(&straighter.path, Connectivity::Four), Ok(18));
+    }
+}
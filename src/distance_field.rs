@@ -0,0 +1,120 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, solve::Connectivity};
+
+// The cheapest cost from source to every cell that can be reached from it, source itself included at 0
+// Runs Dijkstra until the open set is empty rather than stopping at an exit, so it's for shading a whole maze by distance
+// (as a heatmap, say) rather than for finding a path. Weights, step costs and portals all count, just as they do in solve
+// Returns an empty map if source is outside the grid or a wall
+pub fn distance_field(grid: &Grid<Cell>, source: Coordinate, connectivity: Connectivity) -> HashMap<Coordinate, usize> {
+    flood(grid, source, connectivity, false)
+}
+
+// Dijkstra out from source to every cell it can reach, or with backward, to every cell that can reach it, each with what it
+// costs to get from one to the other. Going backward, a step costs the weight of the cell it leaves, as that's the cell
+// a step the other way would be stepping into; the two only differ when there are one-way cells or weighted terrain
+fn flood(grid: &Grid<Cell>, source: Coordinate, connectivity: Connectivity, backward: bool) -> HashMap<Coordinate, usize> {
+    let mut open_set = BinaryHeap::new();
+    let mut best_costs = HashMap::new();
+    let Some(&source_cell) = grid.try_cell_at(source).filter(|cell| cell.cell_type != CellType::Wall) else {
+        return best_costs;
+    };
+    // The grid may still hold costs from an earlier search, so start the source from nothing
+    best_costs.insert(source, 0);
+    open_set.push(Cell { cost: 0, f_score: 0, ..source_cell });
+    while let Some(current_cell) = open_set.pop() {
+        if current_cell.cost > best_costs[&current_cell.coordinate] {
+            continue;
+        }
+        let neighbours = if backward { grid.predecessors(current_cell.coordinate, connectivity) } else { grid.neighbours(current_cell.coordinate, connectivity) };
+        for neighbour in neighbours {
+            let mut neighbour_cell = grid[neighbour];
+            if neighbour_cell.cell_type == CellType::Wall {
+                continue;
+            }
+            let weight = if backward { current_cell.weight } else { neighbour_cell.weight };
+            let tentative_cost = current_cell.cost + connectivity.step_cost_between(grid, current_cell.coordinate, neighbour) * weight;
+            if best_costs.get(&neighbour).is_some_and(|&best_cost| tentative_cost >= best_cost) {
+                continue;
+            }
+            best_costs.insert(neighbour, tentative_cost);
+            neighbour_cell.cost = tentative_cost;
+            neighbour_cell.f_score = tentative_cost;
+            open_set.push(neighbour_cell);
+        }
+    }
+    best_costs
+}
+
+// Every cell on at least one shortest path from the entrance to an exit: the band of cells the solution could run through,
+// where solve_all_shortest would list the paths themselves (and there can be exponentially many of them)
+// A cell is on a shortest path if its distance from the entrance and its distance to a cheapest exit add up to the shortest
+// path's cost; the distances to the exit come from flooding backwards out of it
+// Returns an empty set if no exit can be reached
+pub fn optimal_cells(grid: &Grid<Cell>, connectivity: Connectivity) -> HashSet<Coordinate> {
+    let from_entrance = distance_field(grid, grid.entrance_location, connectivity);
+    let mut cells = HashSet::new();
+    let Some(optimal_cost) = grid.exits.iter().filter_map(|exit| from_entrance.get(exit)).min().copied() else {
+        return cells;
+    };
+    for &exit in grid.exits.iter().filter(|exit| from_entrance.get(exit) == Some(&optimal_cost)) {
+        for (coordinate, to_exit) in flood(grid, exit, connectivity, true) {
+            let Some(&from_start) = from_entrance.get(&coordinate) else {
+                continue;
+            };
+            if from_start + to_exit == optimal_cost {
+                cells.insert(coordinate);
+            }
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{all_shortest::solve_all_shortest, load_maze};
+
+    #[test]
+    fn the_entrance_is_0_and_its_neighbours_1() {
+        let grid = load_maze("maze-Easy");
+        let entrance = grid.entrance();
+        let distances = distance_field(&grid, entrance, Connectivity::Four);
+        assert_eq!(distances[&entrance], 0);
+        let open_neighbours: Vec<Coordinate> = grid.neighbours(entrance, Connectivity::Four).into_iter()
+            .filter(|&neighbour| grid[neighbour].cell_type != CellType::Wall)
+            .collect();
+        assert!(!open_neighbours.is_empty());
+        assert!(open_neighbours.iter().all(|neighbour| distances[neighbour] == 1));
+        assert_eq!(distances[&grid.exit()], 26);
+        // Every other cell is one step further than its nearest neighbour, and no wall is in the field at all
+        for (&coordinate, &distance) in distances.iter().filter(|(&coordinate, _)| coordinate != entrance) {
+            let nearest = grid.neighbours(coordinate, Connectivity::Four).iter().filter_map(|neighbour| distances.get(neighbour)).min().copied();
+            assert_eq!(nearest.map(|nearest| nearest + 1), Some(distance), "{}", coordinate);
+        }
+        assert!(distances.keys().all(|&coordinate| grid[coordinate].cell_type != CellType::Wall));
+    }
+
+    #[test]
+    fn a_source_in_a_wall_reaches_nothing() {
+        let grid = load_maze("maze-Easy");
+        assert!(distance_field(&grid, Coordinate { x: 0, y: 0 }, Connectivity::Four).is_empty());
+    }
+
+    // Both ways round the pillar are shortest, so every cell of both is in the band; the dead end below isn't on either
+    #[test]
+    fn both_routes_round_a_pillar_are_optimal() {
+        let grid: Grid<Cell> = "#####\n#---#\n--#--\n#---#\n#-###\n#####".parse().unwrap();
+        let cells = optimal_cells(&grid, Connectivity::Four);
+        assert_eq!(cells.len(), 10);
+        assert!(!cells.contains(&Coordinate { x: 1, y: 4 }));
+        let on_some_path: HashSet<Coordinate> = solve_all_shortest(&grid, Connectivity::Four, None).into_iter().flatten().collect();
+        assert_eq!(cells, on_some_path);
+    }
+
+    #[test]
+    fn a_sealed_exit_has_no_optimal_cells() {
+        let grid: Grid<Cell> = "#####\n--#--\n#####".parse().unwrap();
+        assert!(optimal_cells(&grid, Connectivity::Four).is_empty());
+    }
+}
@@ -0,0 +1,77 @@
+use std::{collections::HashSet, fmt::Write};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, solve::Connectivity};
+
+impl Grid<Cell> {
+    // The maze as an undirected Graphviz DOT graph, for looking at its structure with graphviz, networkx and the like
+    // Every open cell is a node named "x,y", joined by an edge to each open cell a four-way step away (round the edges too
+    // if the maze wraps). Portals are dashed edges between their two ends, the entrance is filled green and the exits blue,
+    // and a weighted cell carries its weight as a "weight" attribute
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph maze {\n");
+        let open_cells = self.cells.iter().filter(|cell| cell.cell_type != CellType::Wall);
+        for cell in open_cells.clone() {
+            let coordinate = cell.coordinate;
+            let mut attributes = Vec::new();
+            if coordinate == self.entrance_location {
+                attributes.push("style=filled fillcolor=green".to_string());
+            }
+            else if self.exits.contains(&coordinate) {
+                attributes.push("style=filled fillcolor=lightblue".to_string());
+            }
+            if cell.weight > 1 {
+                attributes.push(format!("weight={}", cell.weight));
+            }
+            // Writing to a String can't fail
+            if attributes.is_empty() {
+                let _ = writeln!(dot, "  {};", node_name(coordinate));
+            }
+            else {
+                let _ = writeln!(dot, "  {} [{}];", node_name(coordinate), attributes.join(" "));
+            }
+        }
+        // Each pair of neighbours would otherwise be written twice, once from each end
+        let mut written = HashSet::new();
+        for cell in open_cells {
+            let from = cell.coordinate;
+            for to in self.neighbours(from, Connectivity::Four) {
+                let pair = if (from.y, from.x) <= (to.y, to.x) { (from, to) } else { (to, from) };
+                if self[to].cell_type == CellType::Wall || to == from || !written.insert(pair) {
+                    continue;
+                }
+                let style = if self.portals.get(&from) == Some(&to) { " [style=dashed]" } else { "" };
+                let _ = writeln!(dot, "  {} -- {}{};", node_name(pair.0), node_name(pair.1), style);
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn node_name(coordinate: Coordinate) -> String {
+    format!("\"{},{}\"", coordinate.x, coordinate.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Seven open cells, joined by six steps: two along the top row, two down from it and two along the bottom
+    #[test]
+    fn each_pair_of_neighbours_is_one_edge() {
+        let grid: Grid<Cell> = "#####\n--#--\n#---#\n#####".parse().unwrap();
+        let dot = grid.to_dot();
+        assert_eq!(dot.lines().filter(|line| line.contains(" -- ")).count(), 6);
+        assert_eq!(dot.lines().filter(|line| line.ends_with(';') && !line.contains(" -- ")).count(), 7);
+        assert!(dot.contains("  \"0,1\" -- \"1,1\";\n"));
+        assert!(dot.contains("  \"2,2\" -- \"3,2\";\n"));
+        assert!(dot.contains("  \"0,1\" [style=filled fillcolor=green];\n"));
+        assert!(dot.contains("  \"4,1\" [style=filled fillcolor=lightblue];\n"));
+    }
+
+    #[test]
+    fn portals_are_dashed_edges() {
+        let grid: Grid<Cell> = "#######\n-A#A---\n#######".parse().unwrap();
+        assert!(grid.to_dot().contains("  \"1,1\" -- \"3,1\" [style=dashed];\n"));
+    }
+}
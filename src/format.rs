@@ -0,0 +1,451 @@
+use std::{fs::File, io::{BufRead, BufReader}, iter::Peekable, path::Path, str::{Chars, FromStr}};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, parse::{read_maze_text, GridConfig, MazeError}};
+
+// The ways a maze can be written down
+// Ascii is the usual '#' and '-' text (or whatever characters a GridConfig says). JsonGrid and Csv are grids of 0s and 1s,
+// 0 for open and 1 for a wall: a JSON array of rows, or one comma-separated row per line. Either can mark the entrance with
+// an "S" and the goal with a "G" in place of a 0, and a JSON maze can instead be an object, {"maze": [[...]], "entrance": [x, y], "exit": [x, y]}
+// Without any of those, the first open cell (reading along the rows from the top) is the entrance and the last one is the exit
+// ThinWall mazes draw walls along the edges between cells rather than filling cells in (see grid_from_thin_walls)
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Format {
+    Ascii,
+    JsonGrid,
+    Csv,
+    ThinWall,
+}
+impl Format {
+    // The format a file's name says it's in, if it says at all; a ".gz" on the end is looked past
+    pub fn from_extension(path: &Path) -> Option<Format> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        let name = name.strip_suffix(".gz").unwrap_or(&name);
+        if name.ends_with(".json") {
+            Some(Format::JsonGrid)
+        }
+        else if name.ends_with(".csv") {
+            Some(Format::Csv)
+        }
+        else if name.ends_with(".txt") {
+            Some(Format::Ascii)
+        }
+        else {
+            None
+        }
+    }
+
+    // Guess the format from the maze itself: JSON starts with a bracket or brace, a CSV maze has commas in its first line,
+    // and a thin-wall maze starts with a line of nothing but underscores and spaces along its top
+    // An ASCII maze can't start with any of those, as none of them is a maze character in the default format
+    pub fn sniff(maze_text: &str) -> Format {
+        let first_line = maze_text.lines().find(|line| !line.trim().is_empty()).unwrap_or_default().trim_start();
+        if first_line.starts_with('[') || first_line.starts_with('{') {
+            Format::JsonGrid
+        }
+        else if first_line.chars().all(|char| char == '_' || char == ' ') {
+            Format::ThinWall
+        }
+        else if first_line.contains(',') {
+            Format::Csv
+        }
+        else {
+            Format::Ascii
+        }
+    }
+}
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "ascii" => Ok(Format::Ascii),
+            "json" => Ok(Format::JsonGrid),
+            "csv" => Ok(Format::Csv),
+            "thinwall" => Ok(Format::ThinWall),
+            _ => Err(format!("unknown maze format '{}' (expected ascii, json, csv or thinwall)", name)),
+        }
+    }
+}
+
+impl Grid<Cell> {
+    // Load a maze file in any format: the one given, or else the one its name says, or else whatever its contents look like
+    // A .txt file could hold either kind of text maze, so only JSON and CSV files go by their name alone
+    // config only applies to ASCII mazes
+    pub fn load(path_to_maze: &Path, format: Option<Format>, config: &GridConfig) -> Result<Grid<Cell>, MazeError> {
+        let format = format.or_else(|| Format::from_extension(path_to_maze).filter(|&format| format != Format::Ascii));
+        Grid::from_reader_in(BufReader::new(File::open(path_to_maze)?), format, config)
+    }
+
+    // Build a Grid from a maze read from anywhere, in the format given or, without one, whatever its contents look like
+    pub fn from_reader_in<R: BufRead>(reader: R, format: Option<Format>, config: &GridConfig) -> Result<Grid<Cell>, MazeError> {
+        let maze_text = read_maze_text(reader)?;
+        Grid::from_string_in(&maze_text, format.unwrap_or_else(|| Format::sniff(&maze_text)), config)
+    }
+
+    // Build a Grid from the text of a maze in the given format
+    pub fn from_string_in(maze_text: &str, format: Format, config: &GridConfig) -> Result<Grid<Cell>, MazeError> {
+        match format {
+            Format::Ascii => Grid::from_string_with_config(maze_text, config),
+            Format::JsonGrid => {
+                let mut parser = JsonParser { chars: maze_text.chars().peekable(), depth: 0 };
+                let json = parser.parse()?;
+                parser.skip_whitespace();
+                if let Some(char) = parser.chars.next() {
+                    return Err(MazeError::Malformed(format!("unexpected '{}' after the end of the JSON", char)));
+                }
+                let (rows, entrance, exit) = match json {
+                    Json::Object(fields) => {
+                        let field = |name: &str| fields.iter().find(|(key, _)| key == name).map(|(_, value)| value);
+                        let rows = field("maze").ok_or_else(|| MazeError::Malformed("JSON maze object has no \"maze\" field".to_string()))?;
+                        let entrance = field("entrance").map(json_coordinate).transpose()?;
+                        let exit = field("exit").map(json_coordinate).transpose()?;
+                        (json_rows(rows)?, entrance, exit)
+                    },
+                    json => (json_rows(&json)?, None, None),
+                };
+                grid_from_rows(rows, entrance, exit)
+            },
+            Format::Csv => {
+                let rows = maze_text.lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .enumerate()
+                    .map(|(row, line)| line.split(',').enumerate().map(|(column, value)| cell_char(value.trim(), row, column)).collect())
+                    .collect::<Result<_, _>>()?;
+                grid_from_rows(rows, None, None)
+            },
+            Format::ThinWall => grid_from_thin_walls(maze_text),
+        }
+    }
+}
+
+// Turn a thin-wall maze into a Grid, where walls are drawn between cells rather than filling them:
+//  _   _
+// |_  | |
+// | |_  |
+// |_ _  |
+// The first line is the top of the maze, with an '_' over every cell walled off along the top. Every line after it is a row
+// of cells, starting with a '|' if the row's leftmost cell is walled off on the left; each cell is then two characters, an '_'
+// if it's walled off below and a '|' if it's walled off to the right. Gaps in the outside wall are the entrance and exits
+// The Grid has a cell for every cell, and one for every wall or gap between them, so it's 2w + 1 by 2h + 1 for a maze
+// w by h cells: cell (x, y) of the thin-wall maze is (2x + 1, 2y + 1) in the Grid, and the cells on the path between
+// two cells are the gaps between them
+fn grid_from_thin_walls(maze_text: &str) -> Result<Grid<Cell>, MazeError> {
+    let lines: Vec<Vec<char>> = maze_text.lines().skip_while(|line| line.trim().is_empty()).map(|line| line.trim_end().chars().collect()).collect();
+    let Some(top) = lines.first() else {
+        return Err(MazeError::EmptyFile);
+    };
+    let rows = &lines[1..];
+    if rows.is_empty() {
+        return Err(MazeError::Malformed("a thin-wall maze needs at least one row of cells below its top line".to_string()));
+    }
+    if let Some(char) = lines.iter().flatten().find(|&&char| !"_| ".contains(char)) {
+        return Err(MazeError::Malformed(format!("'{}' isn't part of a thin-wall maze, which only uses '_', '|' and spaces", char)));
+    }
+    // Lines can lose their trailing spaces, so the widest line says how many cells wide the maze is, and a line that stops
+    // short has gaps in the rest of it
+    let width = lines.iter().map(|line| line.len() / 2).max().unwrap_or_default();
+    let at = |line: &[char], index: usize| line.get(index).copied().unwrap_or(' ');
+    let (block_width, block_height) = (2 * width + 1, 2 * rows.len() + 1);
+    let mut blocks = vec![vec!['#'; block_width]; block_height];
+    for x in 0..width {
+        if at(top, 2 * x + 1) != '_' {
+            blocks[0][2 * x + 1] = '-';
+        }
+    }
+    for (y, row) in rows.iter().enumerate() {
+        if at(row, 0) != '|' {
+            blocks[2 * y + 1][0] = '-';
+        }
+        for x in 0..width {
+            blocks[2 * y + 1][2 * x + 1] = '-';
+            if at(row, 2 * x + 1) != '_' {
+                blocks[2 * y + 2][2 * x + 1] = '-';
+            }
+            if at(row, 2 * x + 2) != '|' {
+                blocks[2 * y + 1][2 * x + 2] = '-';
+            }
+        }
+    }
+    let maze_text: Vec<String> = blocks.iter().map(|row| row.iter().collect()).collect();
+    Grid::from_string(&maze_text.join("\n"))
+}
+
+// The character grid_from_rows uses for one cell of a JSON or CSV maze
+fn cell_char(value: &str, row: usize, column: usize) -> Result<char, MazeError> {
+    match value {
+        "0" => Ok('.'),
+        "1" => Ok('#'),
+        "S" | "G" => Ok(value.chars().next().unwrap()),
+        _ => Err(MazeError::Malformed(format!("row {}, column {} is '{}', but cells must be 0, 1, S or G", row, column, value))),
+    }
+}
+
+// Turn a grid of cells into a Grid, by writing it out as text with '#' for walls, '.' for open cells and 'S' and 'G'
+// for the entrance and goal, and parsing that; the ASCII parser then does all the checking, so every format agrees
+// on what a valid maze is. An entrance or exit given separately is marked on the grid first
+fn grid_from_rows(mut rows: Vec<Vec<char>>, entrance: Option<Coordinate>, exit: Option<Coordinate>) -> Result<Grid<Cell>, MazeError> {
+    for (marker, coordinate) in [('S', entrance), ('G', exit)] {
+        let Some(coordinate) = coordinate else {
+            continue;
+        };
+        match rows.get_mut(coordinate.y as usize).and_then(|row| row.get_mut(coordinate.x as usize)) {
+            None => return Err(MazeError::OutOfBounds(coordinate)),
+            Some('#') => return Err(MazeError::InWall(coordinate)),
+            Some(cell) => *cell = marker,
+        }
+    }
+    let has_marker = |rows: &Vec<Vec<char>>, marker: char| rows.iter().flatten().any(|&cell| cell == marker);
+    if !has_marker(&rows, 'S') {
+        if let Some(cell) = rows.iter_mut().flatten().find(|cell| **cell == '.') {
+            *cell = 'S';
+        }
+    }
+    if !has_marker(&rows, 'G') {
+        if let Some(cell) = rows.iter_mut().flatten().rev().find(|cell| **cell == '.') {
+            *cell = 'G';
+        }
+    }
+    let maze_text: Vec<String> = rows.iter().map(|row| row.iter().collect()).collect();
+    Grid::from_string_with(&maze_text.join("\n"), |char| match char {
+        '#' => Some(CellType::Wall),
+        '.' | 'S' | 'G' => Some(CellType::Path),
+        _ => None,
+    })
+}
+
+// A JSON grid's rows, as characters for grid_from_rows
+fn json_rows(json: &Json) -> Result<Vec<Vec<char>>, MazeError> {
+    let Json::Array(rows) = json else {
+        return Err(MazeError::Malformed("JSON maze should be an array of rows".to_string()));
+    };
+    rows.iter().enumerate().map(|(row, cells)| {
+        let Json::Array(cells) = cells else {
+            return Err(MazeError::Malformed(format!("row {} of the JSON maze isn't an array", row)));
+        };
+        cells.iter().enumerate().map(|(column, cell)| match cell {
+            Json::Number(value) | Json::String(value) => cell_char(value, row, column),
+            _ => cell_char("", row, column),
+        }).collect()
+    }).collect()
+}
+
+// An [x, y] pair from a JSON maze object
+fn json_coordinate(json: &Json) -> Result<Coordinate, MazeError> {
+    if let Json::Array(pair) = json {
+        if let [Json::Number(x), Json::Number(y)] = &pair[..] {
+            if let (Ok(x), Ok(y)) = (x.parse(), y.parse()) {
+                return Ok(Coordinate{x, y});
+            }
+        }
+    }
+    Err(MazeError::Malformed("entrance and exit should be [x, y] pairs".to_string()))
+}
+
+// Just enough of JSON to read a maze: numbers are kept as the text they were written as
+enum Json {
+    Number(String),
+    String(String),
+    // true, false or null, none of which a maze has any use for
+    Literal,
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+// How deeply arrays and objects can nest in a JSON maze. A maze itself only needs 3 (an object holding rows of cells), so this
+// leaves plenty of room for fields it doesn't use, while stopping a file of nothing but '['s from recursing until the stack overflows
+const MAX_JSON_DEPTH: usize = 32;
+
+struct JsonParser<'a> {
+    chars: Peekable<Chars<'a>>,
+    // How many arrays and objects the parser is inside
+    depth: usize,
+}
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|char| char.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), MazeError> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(char) if char == expected => Ok(()),
+            Some(char) => Err(MazeError::Malformed(format!("expected '{}' in the JSON, found '{}'", expected, char))),
+            None => Err(MazeError::Malformed(format!("expected '{}', but the JSON ended", expected))),
+        }
+    }
+
+    fn parse(&mut self) -> Result<Json, MazeError> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('[') => {
+                self.chars.next();
+                let values = self.nested(|parser| parser.parse_list(']', JsonParser::parse))?;
+                Ok(Json::Array(values))
+            },
+            Some('{') => {
+                self.chars.next();
+                let fields = self.nested(|parser| parser.parse_list('}', |parser| {
+                    let Json::String(key) = parser.parse()? else {
+                        return Err(MazeError::Malformed("JSON object keys must be strings".to_string()));
+                    };
+                    parser.expect(':')?;
+                    Ok((key, parser.parse()?))
+                }))?;
+                Ok(Json::Object(fields))
+            },
+            Some('"') => {
+                self.chars.next();
+                let mut string = String::new();
+                loop {
+                    match self.chars.next() {
+                        Some('"') => return Ok(Json::String(string)),
+                        Some('\\') => string.extend(self.chars.next()),
+                        Some(char) => string.push(char),
+                        None => return Err(MazeError::Malformed("a string in the JSON is never closed".to_string())),
+                    }
+                }
+            },
+            Some(&char) if char == '-' || char.is_ascii_alphanumeric() => {
+                let mut token = String::new();
+                while let Some(char) = self.chars.next_if(|char| char.is_ascii_alphanumeric() || "+-.".contains(*char)) {
+                    token.push(char);
+                }
+                if char.is_ascii_alphabetic() {
+                    Ok(Json::Literal)
+                }
+                else {
+                    Ok(Json::Number(token))
+                }
+            },
+            Some(&char) => Err(MazeError::Malformed(format!("unexpected '{}' in the JSON", char))),
+            None => Err(MazeError::EmptyFile),
+        }
+    }
+
+    // Parse the inside of an array or object, one level deeper than the parser was
+    fn nested<T>(&mut self, parse_inside: impl FnOnce(&mut Self) -> Result<T, MazeError>) -> Result<T, MazeError> {
+        if self.depth == MAX_JSON_DEPTH {
+            return Err(MazeError::Malformed(format!("the JSON nests more than {} arrays or objects deep", MAX_JSON_DEPTH)));
+        }
+        self.depth += 1;
+        let result = parse_inside(self);
+        self.depth -= 1;
+        result
+    }
+
+    // Comma-separated items up to the closing bracket or brace, whose opening one has already been read
+    fn parse_list<T>(&mut self, close: char, mut item: impl FnMut(&mut Self) -> Result<T, MazeError>) -> Result<Vec<T>, MazeError> {
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&close).is_some() {
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => (),
+                Some(char) if char == close => return Ok(items),
+                Some(char) => return Err(MazeError::Malformed(format!("expected ',' or '{}' in the JSON, found '{}'", close, char))),
+                None => return Err(MazeError::Malformed(format!("expected '{}', but the JSON ended", close))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_maze, solve::{solve, SolveOutcome}};
+
+    // A maze's cells as rows of 0s and 1s, with S and G for its entrance and exit
+    fn cell_values(grid: &Grid<Cell>) -> Vec<Vec<&'static str>> {
+        grid.rows().map(|row| row.iter().map(|cell| match cell.cell_type {
+            CellType::Wall => "1",
+            _ if cell.coordinate == grid.entrance() => "S",
+            _ if cell.coordinate == grid.exit() => "G",
+            _ => "0",
+        }).collect()).collect()
+    }
+
+    fn to_json(grid: &Grid<Cell>) -> String {
+        let rows: Vec<String> = cell_values(grid).iter().map(|row| {
+            let values: Vec<String> = row.iter().map(|&value| if value == "S" || value == "G" { format!("\"{}\"", value) } else { value.to_string() }).collect();
+            format!("[{}]", values.join(", "))
+        }).collect();
+        format!("[\n{}\n]", rows.join(",\n"))
+    }
+
+    fn to_csv(grid: &Grid<Cell>) -> String {
+        cell_values(grid).iter().map(|row| row.join(",")).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn every_format_reads_back_the_same_maze() {
+        let ascii = load_maze("maze-Medium");
+        let config = GridConfig::default();
+        for (format, text) in [(Format::JsonGrid, to_json(&ascii)), (Format::Csv, to_csv(&ascii))] {
+            let grid = Grid::from_string_in(&text, format, &config).unwrap();
+            assert_eq!(grid, ascii, "{:?}", format);
+            // Without saying which format it is, it's worked out from the text
+            assert_eq!(Format::sniff(&text), format);
+            assert_eq!(Grid::from_reader_in(text.as_bytes(), None, &config).unwrap(), ascii, "{:?}", format);
+        }
+        let text = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/mazes/maze-Medium.txt")).unwrap();
+        assert_eq!(Format::sniff(&text), Format::Ascii);
+        assert_eq!(Grid::from_string_in(&text, Format::Ascii, &config).unwrap(), ascii);
+    }
+
+    // Without S and G, the first open cell is the entrance and the last the exit, unless a JSON object says otherwise
+    #[test]
+    fn the_first_and_last_open_cells_are_the_openings_unless_given() {
+        let config = GridConfig::default();
+        let grid = Grid::from_string_in("1,0,1\n1,0,1\n1,0,1", Format::Csv, &config).unwrap();
+        assert_eq!((grid.entrance(), grid.exit()), (Coordinate { x: 1, y: 0 }, Coordinate { x: 1, y: 2 }));
+        let json = "{\"maze\": [[1, 0, 1], [0, 0, 0], [1, 0, 1]], \"entrance\": [0, 1], \"exit\": [2, 1]}";
+        let grid = Grid::from_string_in(json, Format::JsonGrid, &config).unwrap();
+        assert_eq!((grid.entrance(), grid.exit()), (Coordinate { x: 0, y: 1 }, Coordinate { x: 2, y: 1 }));
+    }
+
+    #[test]
+    fn formats_are_named_by_extension() {
+        assert_eq!(Format::from_extension(Path::new("maze.json")), Some(Format::JsonGrid));
+        assert_eq!(Format::from_extension(Path::new("maze.CSV.gz")), Some(Format::Csv));
+        assert_eq!(Format::from_extension(Path::new("maze.txt")), Some(Format::Ascii));
+        assert_eq!(Format::from_extension(Path::new("maze.md")), None);
+    }
+
+    // The maze drawn at grid_from_thin_walls: in at the gap in the top of the middle cell, down a cell, right a cell,
+    // and out through the bottom of the right-hand column
+    #[test]
+    fn a_thin_wall_maze_solves_through_its_gaps() {
+        let maze_text = " _   _\n|_  | |\n| |_  |\n|_ _  |";
+        assert_eq!(Format::sniff(maze_text), Format::ThinWall);
+        let mut grid = Grid::from_string_in(maze_text, Format::ThinWall, &GridConfig::default()).unwrap();
+        assert_eq!((grid.width(), grid.height()), (7, 7));
+        assert_eq!((grid.entrance(), grid.exit()), (Coordinate { x: 3, y: 0 }, Coordinate { x: 5, y: 6 }));
+        let SolveOutcome::Found { path, cost, .. } = solve(&mut grid) else {
+            panic!("no way through the thin-wall maze");
+        };
+        let expected: Vec<Coordinate> = [(3, 0), (3, 1), (3, 2), (3, 3), (4, 3), (5, 3), (5, 4), (5, 5), (5, 6)].iter().map(|&(x, y)| Coordinate { x, y }).collect();
+        assert_eq!((path, cost), (expected, 8));
+    }
+
+    #[test]
+    fn thin_wall_mazes_only_use_underscores_bars_and_spaces() {
+        let error = Grid::from_string_in(" _\n|#|", Format::ThinWall, &GridConfig::default()).unwrap_err();
+        assert!(matches!(error, MazeError::Malformed(_)), "{}", error);
+    }
+
+    // Each '[' used to be a level of recursion, so enough of them overflowed the stack rather than failing to parse
+    #[test]
+    fn deeply_nested_json_is_malformed_rather_than_a_stack_overflow() {
+        let deep = "[".repeat(50_000);
+        let result = Grid::from_reader_in(deep.as_bytes(), None, &GridConfig::default());
+        assert!(matches!(result, Err(MazeError::Malformed(ref message)) if message.contains("nests")), "{:?}", result.err());
+        // As deep as is allowed parses, and only then fails for not being a maze
+        let nested = |depth| Grid::from_string_in(&format!("{}{}", "[".repeat(depth), "]".repeat(depth)), Format::JsonGrid, &GridConfig::default());
+        assert!(matches!(nested(MAX_JSON_DEPTH), Err(MazeError::Malformed(ref message)) if !message.contains("nests")));
+        assert!(matches!(nested(MAX_JSON_DEPTH + 1), Err(MazeError::Malformed(ref message)) if message.contains("nests")));
+    }
+}
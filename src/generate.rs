@@ -0,0 +1,112 @@
+use crate::grid::{Cell, Grid};
+
+// A small splitmix64 generator, so generated mazes are reproducible from their seed without pulling in a rand dependency
+struct SeededRng {
+    state: u64,
+}
+impl SeededRng {
+    fn new(seed: u64) -> SeededRng {
+        SeededRng { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // A random number in 0..bound
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+// Generate a perfect maze (exactly one route between any two open cells) of the given size as text,
+// in the same '#'/'-' format as the files in mazes/
+// The maze is carved with a randomised depth-first search (the "recursive backtracker"), so the same seed always
+// gives the same maze. Open cells sit on odd coordinates; if a dimension is even, the extra row/column stays wall
+pub fn generate_text(width: usize, height: usize, seed: u64) -> String {
+    assert!(width >= 3 && height >= 3, "a maze needs to be at least 3x3 to have anything inside its walls");
+    let mut rng = SeededRng::new(seed);
+    let mut open = vec![vec![false; width]; height];
+    // The rooms we carve between are at odd coordinates, up to the last odd coordinate inside the border
+    let last_room_x = if width.is_multiple_of(2) { width - 3 } else { width - 2 };
+    let last_room_y = if height.is_multiple_of(2) { height - 3 } else { height - 2 };
+
+    open[1][1] = true;
+    let mut stack = vec![(1, 1)];
+    while let Some(&(x, y)) = stack.last() {
+        // Rooms two steps away in each direction that we haven't carved into yet
+        let mut unvisited = Vec::with_capacity(4);
+        if x > 1 && !open[y][x - 2] {
+            unvisited.push((x - 2, y));
+        }
+        if x < last_room_x && !open[y][x + 2] {
+            unvisited.push((x + 2, y));
+        }
+        if y > 1 && !open[y - 2][x] {
+            unvisited.push((x, y - 2));
+        }
+        if y < last_room_y && !open[y + 2][x] {
+            unvisited.push((x, y + 2));
+        }
+        if unvisited.is_empty() {
+            // Dead end, so backtrack
+            stack.pop();
+            continue;
+        }
+        let (next_x, next_y) = unvisited[rng.below(unvisited.len())];
+        // Knock down the wall between the two rooms, then carry on from the new one
+        open[(y + next_y) / 2][(x + next_x) / 2] = true;
+        open[next_y][next_x] = true;
+        stack.push((next_x, next_y));
+    }
+
+    // The entrance goes in the top wall above the first room and the exit in the bottom wall below the last one,
+    // carving through the spare row first if the height is even
+    open[0][1] = true;
+    for row in open.iter_mut().skip(last_room_y + 1) {
+        row[last_room_x] = true;
+    }
+
+    let mut maze_text = String::with_capacity(width * height * 2 + height);
+    for row in open {
+        for is_open in row {
+            maze_text.push(if is_open { '-' } else { '#' });
+            maze_text.push(' ');
+        }
+        maze_text.push('\n');
+    }
+    maze_text
+}
+
+// Generate a perfect maze of the given size and load it as a Grid; see generate_text
+pub fn generate(width: usize, height: usize, seed: u64) -> Grid<Cell> {
+    generate_text(width, height, seed).parse().expect("generated mazes always have an entrance, an exit and even rows")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve::solve;
+
+    #[test]
+    fn generated_mazes_are_the_size_asked_for_and_solvable() {
+        for (width, height) in [(3, 3), (4, 3), (3, 4), (21, 11), (40, 30)] {
+            for seed in 0..5 {
+                let mut grid = generate(width, height, seed);
+                assert_eq!((grid.width(), grid.height()), (width, height));
+                assert!(grid.is_perfect(), "{}x{} seed {} isn't perfect", width, height, seed);
+                assert!(solve(&mut grid).is_solved(), "{}x{} seed {} can't be solved", width, height, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_maze() {
+        assert_eq!(generate_text(31, 21, 7), generate_text(31, 21, 7));
+        assert_ne!(generate_text(31, 21, 7), generate_text(31, 21, 8));
+    }
+}
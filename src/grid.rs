@@ -0,0 +1,628 @@
+use std::{collections::{HashMap, HashSet, VecDeque}, fmt, ops::Index, str::FromStr};
+
+use crate::solve::Connectivity;
+
+// A cell's column and row, counting from the top left
+// u32 rather than usize keeps Cells (which hold one for themselves and another for their parent) small on huge mazes;
+// no grid is more than MAX_SIDE cells across or down, so every coordinate inside one fits
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Coordinate {
+    pub x: u32,
+    pub y: u32,
+}
+impl Coordinate {
+    // The coordinate of a column and row counted as usize, the way loops over a grid count them
+    // Only for cells inside a grid, where they always fit; anything bigger is a bug, caught in debug builds
+    pub(crate) fn new(x: usize, y: usize) -> Coordinate {
+        debug_assert!(x <= MAX_SIDE && y <= MAX_SIDE, "({}, {}) is too far out for a Coordinate", x, y);
+        Coordinate{x: x as u32, y: y as u32}
+    }
+}
+// The most cells a maze can have across or down, so that a Coordinate can reach every one of them
+pub const MAX_SIDE: usize = u32::MAX as usize;
+// Coordinates are printed as (x, y): column first, then row, counting from the top left
+// Cells are stored row by row, so it's easy to get these the wrong way round; everything shown to the user should go through here
+impl fmt::Display for Coordinate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+// Read a coordinate written as x,y, the way it's given on the command line
+impl FromStr for Coordinate {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let bad_coordinate = || format!("bad coordinate '{}' (expected x,y)", text);
+        let (x, y) = text.split_once(',').ok_or_else(bad_coordinate)?;
+        Ok(Coordinate {
+            x: x.trim().parse().map_err(|_| bad_coordinate())?,
+            y: y.trim().parse().map_err(|_| bad_coordinate())?,
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CellType {
+    Entrance,
+    Exit,
+    Wall,
+    Path,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cell {
+    pub(crate) cell_type: CellType,
+    pub(crate) coordinate: Coordinate,
+    // The cell the search reached this one from; None until it's reached, and always None for where the search started,
+    // so following parents back from any cell the search reached ends at the start
+    pub(crate) parent_coord: Option<Coordinate>,
+    // The heuristic's estimate of the cost from here to the exit
+    pub(crate) h: usize,
+    pub(crate) cost: usize,
+    pub(crate) f_score: usize,
+    // How much it costs to step into this cell; plain paths cost 1, weighted terrain costs more
+    pub(crate) weight: usize,
+    // For a one-way cell, the only way a step can go into it or out of it; None for any other cell
+    pub(crate) direction: Option<Direction>,
+}
+// define ordering so that we can use Cells in a BinaryHeap
+// we order on the f-score (cost + h) so the heap pops the most promising cell first, which is what makes this A*
+// Ties go to the cell the heuristic puts closest to the exit, then to the one nearest the top left (row first, then column),
+// so the same maze always pops its cells in the same order and gives back the same path
+impl Ord for Cell {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.f_score, self.h, self.coordinate.y, self.coordinate.x)
+            .cmp(&(other.f_score, other.h, other.coordinate.y, other.coordinate.x))
+            .reverse()
+    }
+}
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Cell {
+    // define a constructor for a Cell
+    pub(crate) fn new(coordinate: Coordinate, cell_type: CellType) -> Cell {
+        Cell {
+            cell_type,
+            coordinate,
+            parent_coord: None,
+            h: 0,
+            cost: 0,    // we leave cost at 0 so that if something goes wrong, the cost is still an underestimate and therefore
+                        // an admissible heuristic for A*
+            f_score: 0,
+            weight: 1,
+            direction: None,
+        }
+    }
+}
+
+// The four ways a step can go; north is up the maze, towards row 0
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+    // The direction an arrow in a maze file points, for one-way cells: '^', 'v', '<' or '>'
+    pub(crate) fn from_arrow(char: char) -> Option<Direction> {
+        match char {
+            '^' => Some(Direction::North),
+            '>' => Some(Direction::East),
+            'v' => Some(Direction::South),
+            '<' => Some(Direction::West),
+            _ => None,
+        }
+    }
+
+    // How far across and down the maze a step this way goes
+    fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+        }
+    }
+}
+
+// The order Grid::neighbours lists a cell's straight neighbours in, which is the order every search tries them in
+// BFS and DFS keep to it, so it decides which of several equally good paths they find; the algorithms that sort their open set
+// (A* and the like) break ties on the cells themselves instead, so it makes no difference to them
+// Diagonal neighbours always come after the straight ones
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NeighbourOrder(pub [Direction; 4]);
+// West, east, north, south: the order neighbours have always come in
+impl Default for NeighbourOrder {
+    fn default() -> Self {
+        NeighbourOrder([Direction::West, Direction::East, Direction::North, Direction::South])
+    }
+}
+// Read an order written as the four compass letters, each once, like "NESW" or "N,E,S,W"
+impl FromStr for NeighbourOrder {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let bad_order = || format!("bad neighbour order '{}' (expected each of N, E, S and W once, e.g. NESW)", text);
+        let directions = text.chars().filter(|&char| char != ',').map(|char| match char.to_ascii_uppercase() {
+            'N' => Ok(Direction::North),
+            'E' => Ok(Direction::East),
+            'S' => Ok(Direction::South),
+            'W' => Ok(Direction::West),
+            _ => Err(bad_order()),
+        }).collect::<Result<Vec<Direction>, String>>()?;
+        let order: [Direction; 4] = directions.try_into().map_err(|_| bad_order())?;
+        if order.iter().collect::<HashSet<_>>().len() != 4 {
+            return Err(bad_order());
+        }
+        Ok(NeighbourOrder(order))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grid<Cell> {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) entrance_location: Coordinate,
+    // Every exit in the maze, in the order they appear in the file; mazes that aren't "perfect" can have more than one
+    pub(crate) exits: Vec<Coordinate>,
+    // Each end of every portal, mapped to the other end; stepping onto one end can carry on straight to the other
+    // JSON keys have to be strings, so this is saved as a list of pairs rather than as a map
+    #[cfg_attr(feature = "serde", serde(with = "portal_pairs"))]
+    pub(crate) portals: HashMap<Coordinate, Coordinate>,
+    pub(crate) cells: Vec<Cell>,
+    // What a step across the maze and a step up or down it cost, before any terrain weight; both are 1 unless set otherwise
+    pub(crate) cost_x: usize,
+    pub(crate) cost_y: usize,
+    // Whether stepping off one edge comes back on at the opposite one, as if the maze were drawn on a torus
+    pub(crate) toroidal: bool,
+    pub(crate) neighbour_order: NeighbourOrder,
+}
+impl Grid<Cell> {
+    // How many cells wide the maze is
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    // How many cells tall the maze is
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn entrance(&self) -> Coordinate {
+        self.entrance_location
+    }
+
+    // The first exit in the maze, reading row by row from the top; every maze has at least one
+    pub fn exit(&self) -> Coordinate {
+        self.exits[0]
+    }
+
+    // Every exit in the maze, for mazes with more than one
+    pub fn exits(&self) -> &[Coordinate] {
+        &self.exits
+    }
+
+    // Make steps up and down the maze cost differently from steps across it, for mazes where one way is harder going
+    // Panics if either cost is 0, since the heuristics count on every step costing something
+    pub fn set_step_costs(&mut self, cost_x: usize, cost_y: usize) {
+        assert!(cost_x > 0 && cost_y > 0, "step costs must be at least 1");
+        self.cost_x = cost_x;
+        self.cost_y = cost_y;
+    }
+
+    // What a step across the maze and a step up or down it cost, as (cost_x, cost_y)
+    pub fn step_costs(&self) -> (usize, usize) {
+        (self.cost_x, self.cost_y)
+    }
+
+    // Make the maze wrap round, so stepping off the right edge comes back on at the left, and off the bottom back on at the top
+    pub fn set_toroidal(&mut self, toroidal: bool) {
+        self.toroidal = toroidal;
+    }
+
+    pub fn is_toroidal(&self) -> bool {
+        self.toroidal
+    }
+
+    // Change the order neighbours are tried in (see NeighbourOrder)
+    pub fn set_neighbour_order(&mut self, order: NeighbourOrder) {
+        self.neighbour_order = order;
+    }
+
+    pub fn neighbour_order(&self) -> NeighbourOrder {
+        self.neighbour_order
+    }
+
+    // How far apart two coordinates are across and up or down the maze, taking the shorter way round if the maze wraps
+    pub(crate) fn axis_distances(&self, from: Coordinate, to: Coordinate) -> (usize, usize) {
+        let (dx, dy) = (from.x.abs_diff(to.x) as usize, from.y.abs_diff(to.y) as usize);
+        if self.toroidal {
+            (dx.min(self.width - dx), dy.min(self.height - dy))
+        }
+        else {
+            (dx, dy)
+        }
+    }
+
+    // The coordinate dx across and dy down from coordinate, wrapping round the edges if the maze wraps,
+    // or None if that's off the edge of a maze that doesn't
+    fn offset(&self, coordinate: Coordinate, dx: isize, dy: isize) -> Option<Coordinate> {
+        let (x, y) = (coordinate.x as isize + dx, coordinate.y as isize + dy);
+        let (width, height) = (self.width as isize, self.height as isize);
+        if self.toroidal {
+            Some(Coordinate::new(x.rem_euclid(width) as usize, y.rem_euclid(height) as usize))
+        }
+        else if (0..width).contains(&x) && (0..height).contains(&y) {
+            Some(Coordinate::new(x as usize, y as usize))
+        }
+        else {
+            None
+        }
+    }
+
+    // Whether blocked rules out the step from a cell to one of its neighbours, as if every blocked cell were a wall:
+    // either the step goes into a blocked cell, or it's a diagonal step squeezing past one at its corner
+    pub(crate) fn step_blocked(&self, from: Coordinate, to: Coordinate, blocked: &HashSet<Coordinate>) -> bool {
+        if blocked.contains(&to) {
+            return true;
+        }
+        if blocked.is_empty() || self.portals.get(&from) == Some(&to) {
+            return false;
+        }
+        // The cells beside a diagonal step are the ones a step from both of its ends; a step straight across has none
+        let beside = |coordinate: Coordinate| Direction::ALL.into_iter().filter_map(move |direction| {
+            let (dx, dy) = direction.offset();
+            self.offset(coordinate, dx, dy)
+        });
+        beside(from).any(|coordinate| blocked.contains(&coordinate) && beside(to).any(|other| other == coordinate))
+    }
+
+    // Where a coordinate's cell is in cells, which are stored row by row
+    // Everything that turns coordinates into indexes goes through here (and back through coordinate_of),
+    // so there's only one place x and y could get swapped
+    pub fn index_of(&self, coordinate: Coordinate) -> usize {
+        coordinate.y as usize * self.width + coordinate.x as usize
+    }
+
+    // The coordinate of the cell at index in cells; the reverse of index_of
+    pub fn coordinate_of(&self, index: usize) -> Coordinate {
+        Coordinate::new(index % self.width, index / self.width)
+    }
+
+    // Whether coordinate is in the outermost ring of cells, where a maze's openings usually are
+    pub(crate) fn on_border(&self, coordinate: Coordinate) -> bool {
+        coordinate.x == 0 || coordinate.y == 0 || coordinate.x as usize == self.width - 1 || coordinate.y as usize == self.height - 1
+    }
+
+    // Panics if the coordinate is outside the grid; use try_cell_at near the borders
+    pub fn cell_at(&self, coordinate: Coordinate) -> &Cell {
+        &self.cells[self.index_of(coordinate)]
+    }
+
+    pub fn cell_at_mut(&mut self, coordinate: Coordinate) -> &mut Cell {
+        let index = self.index_of(coordinate);
+        &mut self.cells[index]
+    }
+
+    // The cell at a coordinate, or None if the coordinate is outside the grid
+    pub fn try_cell_at(&self, coordinate: Coordinate) -> Option<&Cell> {
+        if (coordinate.x as usize) < self.width && (coordinate.y as usize) < self.height {
+            Some(self.cell_at(coordinate))
+        }
+        else {
+            None
+        }
+    }
+
+    // The coordinates a single step away from coordinate, staying inside the grid (or wrapping round it, if it's toroidal)
+    // Walls aren't filtered out here, apart from the ones that would block a diagonal step
+    // A portal's partner counts as a neighbour too, even though it's usually nowhere near
+    // Steps a one-way cell doesn't allow are left out: out of one other than its own way, or into one other than its own way
+    // The straight neighbours come first, in the grid's neighbour order, then the diagonals, then any portal partner
+    pub fn neighbours(&self, coordinate: Coordinate, connectivity: Connectivity) -> Vec<Coordinate> {
+        let mut neighbours = self.adjacent(coordinate, connectivity);
+        neighbours.retain(|&neighbour| self.one_way_allows(coordinate, neighbour));
+        neighbours
+    }
+
+    // The cells a single step can come into coordinate from: the same as its neighbours, unless there are one-way cells
+    // For searching backwards from the exit
+    pub(crate) fn predecessors(&self, coordinate: Coordinate, connectivity: Connectivity) -> Vec<Coordinate> {
+        let mut predecessors = self.adjacent(coordinate, connectivity);
+        predecessors.retain(|&predecessor| self.one_way_allows(predecessor, coordinate));
+        predecessors
+    }
+
+    // Whether the maze has any one-way cells, which the searches that rely on being able to step back the way they came can't handle
+    pub(crate) fn has_one_way_cells(&self) -> bool {
+        self.cells.iter().any(|cell| cell.direction.is_some())
+    }
+
+    // Whether a step from one cell to the next is allowed by any one-way cell at either end: it has to go the cell's way,
+    // so a diagonal step or a trip through a portal can't go into or out of one at all
+    fn one_way_allows(&self, from: Coordinate, to: Coordinate) -> bool {
+        let (from_direction, to_direction) = (self[from].direction, self[to].direction);
+        if from_direction.is_none() && to_direction.is_none() {
+            return true;
+        }
+        let step = Direction::ALL.into_iter().find(|direction| {
+            let (dx, dy) = direction.offset();
+            self.offset(from, dx, dy) == Some(to)
+        });
+        step.is_some() && [from_direction, to_direction].into_iter().flatten().all(|direction| Some(direction) == step)
+    }
+
+    // neighbours, before one-way cells have their say
+    fn adjacent(&self, coordinate: Coordinate, connectivity: Connectivity) -> Vec<Coordinate> {
+        let mut neighbours: Vec<Coordinate> = self.neighbour_order.0.into_iter()
+            .filter_map(|direction| {
+                let (dx, dy) = direction.offset();
+                self.offset(coordinate, dx, dy)
+            })
+            .collect();
+        if connectivity == Connectivity::Eight {
+            // A diagonal step is only allowed if both the orthogonal cells beside it are open, so we never squeeze between
+            // two walls that only touch at their corners
+            let is_open = |dx: isize, dy: isize| self.offset(coordinate, dx, dy).is_some_and(|beside| self[beside].cell_type != CellType::Wall);
+            for (dx, dy) in [(-1, -1), (1, -1), (-1, 1), (1, 1)] {
+                if let Some(diagonal) = self.offset(coordinate, dx, dy).filter(|_| is_open(dx, 0) && is_open(0, dy)) {
+                    neighbours.push(diagonal);
+                }
+            }
+        }
+        if let Some(&partner) = self.portals.get(&coordinate) {
+            neighbours.push(partner);
+        }
+        neighbours
+    }
+
+    // Every open cell that can be reached from start, including start itself, found by flooding outwards over non-wall cells
+    // A diagonal step needs both the cells beside it open, so allowing diagonals can't reach anything more than this does
+    // Useful for finding parts of a maze walled off from the rest, or for checking an exit can be reached at all before searching
+    // Empty if start is outside the grid or a wall
+    pub fn reachable_from(&self, start: Coordinate) -> HashSet<Coordinate> {
+        if self.try_cell_at(start).is_none_or(|cell| cell.cell_type == CellType::Wall) {
+            return HashSet::new();
+        }
+        let mut reached = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(coordinate) = queue.pop_front() {
+            for neighbour in self.neighbours(coordinate, Connectivity::Four) {
+                if self[neighbour].cell_type != CellType::Wall && reached.insert(neighbour) {
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        reached
+    }
+
+    // Whether b can be reached from a, flooding out from a as reachable_from does but stopping as soon as b turns up
+    // For yes-or-no questions asked over and over, where solving would build a path only to throw it away
+    // False if either is outside the grid or a wall; a cell is always connected to itself if it's open
+    pub fn connected(&self, a: Coordinate, b: Coordinate) -> bool {
+        self.cells_flooded_to(a, b).is_some()
+    }
+
+    // How many cells connected floods through, a included, before it reaches b, or None if it never does
+    fn cells_flooded_to(&self, a: Coordinate, b: Coordinate) -> Option<usize> {
+        if [a, b].iter().any(|&coordinate| self.try_cell_at(coordinate).is_none_or(|cell| cell.cell_type == CellType::Wall)) {
+            return None;
+        }
+        // A flag a cell rather than a HashSet, as this is meant to be cheap enough to call in a loop
+        let mut reached = vec![false; self.cells.len()];
+        reached[self.index_of(a)] = true;
+        let mut queue = VecDeque::from([a]);
+        let mut flooded = 0;
+        while let Some(coordinate) = queue.pop_front() {
+            flooded += 1;
+            if coordinate == b {
+                return Some(flooded);
+            }
+            for neighbour in self.neighbours(coordinate, Connectivity::Four) {
+                let index = self.index_of(neighbour);
+                if self[neighbour].cell_type != CellType::Wall && !reached[index] {
+                    reached[index] = true;
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        None
+    }
+
+    // Put every cell's search fields back to how Cell::new left them, so a search isn't misled by what an earlier one left behind
+    // The cell types, weights, directions and so on read from the maze are kept
+    pub fn reset(&mut self) {
+        for cell in &mut self.cells {
+            *cell = Cell { weight: cell.weight, direction: cell.direction, ..Cell::new(cell.coordinate, cell.cell_type) };
+        }
+    }
+
+    // The cells a row at a time, from the top of the maze down, each row exactly width cells long
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.chunks_exact(self.width)
+    }
+}
+// allow grid[coordinate] as shorthand for grid.cell_at(coordinate)
+impl Index<Coordinate> for Grid<Cell> {
+    type Output = Cell;
+
+    fn index(&self, coordinate: Coordinate) -> &Cell {
+        self.cell_at(coordinate)
+    }
+}
+
+// (De)serialize the portal map as a list of [from, to] pairs, since a Coordinate can't be a JSON object key
+#[cfg(feature = "serde")]
+mod portal_pairs {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Coordinate;
+
+    pub fn serialize<S: Serializer>(portals: &HashMap<Coordinate, Coordinate>, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut pairs: Vec<(&Coordinate, &Coordinate)> = portals.iter().collect();
+        // HashMap order changes from run to run; sort so the same grid always saves the same way
+        pairs.sort_by_key(|(from, _)| (from.y, from.x));
+        pairs.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<Coordinate, Coordinate>, D::Error> {
+        Ok(Vec::<(Coordinate, Coordinate)>::deserialize(deserializer)?.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_maze, solve::{solve, SolveOutcome}};
+
+    #[test]
+    fn rows_are_as_many_as_the_height_and_as_long_as_the_width() {
+        let grid = load_maze("maze-Medium");
+        assert_eq!(grid.rows().count(), grid.height);
+        assert!(grid.rows().all(|row| row.len() == grid.width));
+        // Each row holds the cells with that y, left to right
+        for (y, row) in grid.rows().enumerate() {
+            assert!(row.iter().enumerate().all(|(x, cell)| cell.coordinate == Coordinate { x: x as u32, y: y as u32 }));
+        }
+    }
+
+    #[test]
+    fn reachable_from_leaves_out_a_walled_off_pocket() {
+        let grid: Grid<Cell> = "#######\n------#\n####-##\n#-#----\n#######".parse().unwrap();
+        let pocket = Coordinate { x: 1, y: 3 };
+        let reached = grid.reachable_from(grid.entrance());
+        assert_eq!(reached.len(), 11);
+        assert!(reached.contains(&grid.exit()));
+        assert!(!reached.contains(&pocket));
+        assert_eq!(grid.reachable_from(pocket), HashSet::from([pocket]));
+    }
+
+    // Flooding out of a wall or from off the grid would pick up cells next to it that start can't really reach
+    #[test]
+    fn nothing_is_reachable_from_a_wall_or_outside_the_grid() {
+        let grid: Grid<Cell> = "#####\n-----\n#####".parse().unwrap();
+        assert!(grid.reachable_from(Coordinate { x: 0, y: 0 }).is_empty());
+        assert!(grid.reachable_from(Coordinate { x: 5, y: 1 }).is_empty());
+        assert_eq!(grid.reachable_from(Coordinate { x: 4, y: 1 }).len(), 5);
+    }
+
+    #[test]
+    fn reset_forgets_a_search_but_keeps_the_maze() {
+        let fresh: Grid<Cell> = "#####\n-1-9-\n#####".parse().unwrap();
+        let mut grid = fresh.clone();
+        assert!(solve(&mut grid).is_solved());
+        assert_ne!(grid, fresh);
+        grid.reset();
+        assert_eq!(grid, fresh);
+    }
+
+    // A solved grid, portals and weights and all, comes back from JSON just as it went in, search state included
+    #[cfg(feature = "serde")]
+    #[test]
+    fn a_solved_grid_round_trips_through_serde() {
+        let mut grid: Grid<Cell> = "#######\n-A-#-A-\n#-9#>-#\n#######".parse().unwrap();
+        let (solution, _) = crate::solve::solve_with(&mut grid, crate::solve::Algorithm::AStar, Connectivity::Four, crate::solve::Heuristic::Manhattan, None);
+        let solution = solution.unwrap();
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(serde_json::from_str::<Grid<Cell>>(&json).unwrap(), grid);
+        let json = serde_json::to_string(&solution).unwrap();
+        assert_eq!(serde_json::from_str::<crate::solve::Solution>(&json).unwrap(), solution);
+    }
+
+    #[test]
+    fn coordinates_print_x_then_y() {
+        assert_eq!(Coordinate { x: 3, y: 7 }.to_string(), "(3, 7)");
+        // Wider than it is tall, with the entrance in the left wall and the exit in the bottom one, so swapping x and y shows
+        let grid: Grid<Cell> = "######\n-----#\n####-#".parse().unwrap();
+        assert_eq!((grid.entrance().to_string(), grid.exit().to_string()), ("(0, 1)".to_string(), "(4, 2)".to_string()));
+    }
+
+    #[test]
+    fn coordinates_read_back_as_x_then_y() {
+        assert_eq!("4, 2".parse(), Ok(Coordinate { x: 4, y: 2 }));
+        assert!("4".parse::<Coordinate>().is_err());
+    }
+
+    // Walls are still neighbours; it's the edge of the maze that cuts them down
+    #[test]
+    fn corners_edges_and_the_middle_have_two_three_and_four_neighbours() {
+        let grid = load_maze("maze-Easy");
+        let (right, bottom) = (grid.width as u32 - 1, grid.height as u32 - 1);
+        for (x, y, expected) in [(0, 0, 2), (right, bottom, 2), (0, bottom, 2), (5, 0, 3), (right, 4, 3), (5, 4, 4)] {
+            let neighbours = grid.neighbours(Coordinate { x, y }, Connectivity::Four);
+            assert_eq!(neighbours.len(), expected, "({}, {}): {:?}", x, y, neighbours);
+            assert!(neighbours.iter().all(|&neighbour| matches!(grid.axis_distances(Coordinate { x, y }, neighbour), (1, 0) | (0, 1))));
+        }
+        // Diagonals are only there with both the cells beside them open, so count them on an open field
+        let field: Grid<Cell> = "-----\n-----\n-----\n-----".parse().unwrap();
+        for (x, y, expected) in [(0, 0, 3), (4, 3, 3), (2, 0, 5), (0, 2, 5), (2, 1, 8)] {
+            assert_eq!(field.neighbours(Coordinate { x, y }, Connectivity::Eight).len(), expected, "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn equal_f_scores_pop_nearest_the_exit_then_top_left_first() {
+        let cell = |x, y, f_score, h| Cell { f_score, h, ..Cell::new(Coordinate { x, y }, CellType::Path) };
+        let mut heap = std::collections::BinaryHeap::from([cell(0, 1, 5, 2), cell(3, 0, 5, 1), cell(1, 0, 5, 2), cell(0, 0, 4, 4)]);
+        let order: Vec<(u32, u32)> = std::iter::from_fn(|| heap.pop()).map(|cell| (cell.coordinate.x, cell.coordinate.y)).collect();
+        assert_eq!(order, [(0, 0), (3, 0), (1, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn neighbour_orders_need_each_direction_once() {
+        assert_eq!("N,E,S,W".parse(), Ok(NeighbourOrder(Direction::ALL)));
+        for bad_order in ["NESWN", "NES", "NNSW", "NESX"] {
+            assert!(bad_order.parse::<NeighbourOrder>().is_err(), "{}", bad_order);
+        }
+    }
+
+    #[test]
+    fn connected_is_true_within_a_region_and_false_across_a_wall() {
+        let grid: Grid<Cell> = "#######\n------#\n####-##\n#-#----\n#######".parse().unwrap();
+        let (inside, pocket) = (Coordinate { x: 4, y: 2 }, Coordinate { x: 1, y: 3 });
+        assert!(grid.connected(grid.entrance(), inside));
+        assert!(grid.connected(inside, grid.exit()));
+        assert!(grid.connected(pocket, pocket));
+        assert!(!grid.connected(grid.entrance(), pocket));
+        assert!(!grid.connected(grid.entrance(), Coordinate { x: 0, y: 0 }));
+    }
+
+    // Next door to the entrance of maze-Medium is found within a few cells, rather than after flooding through its 9,102 open cells
+    #[test]
+    fn connected_stops_as_soon_as_it_gets_there() {
+        let grid = load_maze("maze-Medium");
+        let entrance = grid.entrance();
+        let next_door = grid.neighbours(entrance, Connectivity::Four).into_iter().find(|&neighbour| grid[neighbour].cell_type != CellType::Wall).unwrap();
+        assert!(grid.cells_flooded_to(entrance, next_door).is_some_and(|flooded| flooded <= 5));
+        assert!(grid.cells_flooded_to(entrance, grid.exit()).is_some_and(|flooded| flooded > 1000));
+    }
+
+    // The arrow in the middle of the top corridor only lets you through westwards, so going east means the long way round
+    #[test]
+    fn a_one_way_cell_cant_be_entered_against_its_arrow() {
+        let maze = "#######\n---<---\n#-###-#\n#-----#\n#######";
+        let grid: Grid<Cell> = maze.parse().unwrap();
+        let (arrow, west, east) = (Coordinate { x: 3, y: 1 }, Coordinate { x: 2, y: 1 }, Coordinate { x: 4, y: 1 });
+        assert!(!grid.neighbours(west, Connectivity::Four).contains(&arrow));
+        assert!(grid.neighbours(east, Connectivity::Four).contains(&arrow));
+        assert_eq!(grid.neighbours(arrow, Connectivity::Four), [west]);
+        assert_eq!(grid.predecessors(arrow, Connectivity::Four), [east]);
+        assert!(matches!(solve(&mut grid.clone()), SolveOutcome::Found { cost: 10, .. }));
+        let mut with_the_arrow: Grid<Cell> = maze.replace('<', ">").parse().unwrap();
+        assert!(matches!(solve(&mut with_the_arrow), SolveOutcome::Found { cost: 6, .. }));
+    }
+}
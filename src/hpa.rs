@@ -0,0 +1,243 @@
+use std::{cmp::Reverse, collections::{BinaryHeap, HashMap}, time::Instant};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, solve::{solve_with, Algorithm, Connectivity, Heuristic, SearchStats, Solution}};
+
+// Hierarchical path-finding A* (HPA*) for very large mazes, moving four ways
+// The grid is cut into cluster_size x cluster_size clusters. Wherever two neighbouring clusters share an open stretch of
+// border, a cell on each side of it becomes a node of a much smaller abstract graph, joined to the node across the border
+// by a single step and to every other node in its own cluster by the cheapest route that stays inside the cluster.
+// A* runs over that graph from the entrance to an exit, and each edge of the route it finds is then refined back into cells
+// Paths are near-optimal rather than shortest: routes are forced through the chosen border cells, and can't wander out
+// of a cluster and back between two of its nodes. Mazes of only a few clusters, with portals or one-way cells, or that
+// wrap round are solved with plain A*
+pub fn solve_hpa(grid: &mut Grid<Cell>, cluster_size: usize) -> (Option<Solution>, SearchStats) {
+    let cluster_size = cluster_size.max(2);
+    if !grid.portals.is_empty() || grid.has_one_way_cells() || grid.toroidal || (grid.width <= 2 * cluster_size && grid.height <= 2 * cluster_size) {
+        return solve_with(grid, Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, None);
+    }
+    let start_time = Instant::now();
+    let mut stats = SearchStats::default();
+    let graph = AbstractGraph::new(grid, cluster_size, &mut stats);
+
+    // A* over the abstract graph; every edge costs at least as much as the straight-line steps it covers, so Manhattan is admissible
+    let start = graph.index[&grid.entrance_location];
+    let estimate = |node: usize| Heuristic::Manhattan.estimate_nearest(grid, graph.nodes[node], &grid.exits, Connectivity::Four);
+    let mut open_set = BinaryHeap::from([Reverse((estimate(start), start))]);
+    let mut best_costs = HashMap::from([(start, 0)]);
+    let mut parents: HashMap<usize, usize> = HashMap::new();
+    let mut exit_reached = None;
+    while let Some(Reverse((f_score, node))) = open_set.pop() {
+        let cost = best_costs[&node];
+        // A stale copy left behind when a cheaper route to node was found
+        if f_score > cost + estimate(node) {
+            continue;
+        }
+        if grid.exits.contains(&graph.nodes[node]) {
+            exit_reached = Some(node);
+            break;
+        }
+        stats.nodes_expanded += 1;
+        for &(neighbour, edge_cost) in &graph.edges[node] {
+            let tentative_cost = cost + edge_cost;
+            if best_costs.get(&neighbour).is_some_and(|&best_cost| tentative_cost >= best_cost) {
+                continue;
+            }
+            best_costs.insert(neighbour, tentative_cost);
+            parents.insert(neighbour, node);
+            open_set.push(Reverse((tentative_cost + estimate(neighbour), neighbour)));
+        }
+        stats.max_frontier_size = stats.max_frontier_size.max(open_set.len());
+    }
+    let Some(exit_node) = exit_reached else {
+        stats.elapsed = start_time.elapsed();
+        return (None, stats);
+    };
+
+    // Refine the abstract route into cells: a step across a border is already a single step,
+    // and an edge within a cluster is searched for again inside that cluster
+    let mut route = vec![exit_node];
+    while let Some(&parent) = parents.get(route.last().unwrap()) {
+        route.push(parent);
+    }
+    route.reverse();
+    let mut path = vec![grid.entrance_location];
+    for pair in route.windows(2) {
+        let (from, to) = (graph.nodes[pair[0]], graph.nodes[pair[1]]);
+        if graph.cluster_of(from) != graph.cluster_of(to) {
+            path.push(to);
+            continue;
+        }
+        let costs = graph.search_cluster(grid, from, &mut stats);
+        // Walk back from to, stopping short of from since it's already on the path
+        let mut segment = Vec::new();
+        let mut current = to;
+        while current != from {
+            segment.push(current);
+            current = costs[&current].1;
+        }
+        path.extend(segment.into_iter().rev());
+    }
+    let exit = graph.nodes[exit_node];
+    stats.path_length = path.len();
+    stats.elapsed = start_time.elapsed();
+    (Some(Solution { path, exit, cost: best_costs[&exit_node] }), stats)
+}
+
+// The clusters' border crossings, plus the entrance and exits, and the costs of getting between them
+struct AbstractGraph {
+    cluster_size: usize,
+    nodes: Vec<Coordinate>,
+    index: HashMap<Coordinate, usize>,
+    // Each node's outgoing edges, as (node, cost); costs depend on the weight of the cell stepped into, so they can differ each way
+    edges: Vec<Vec<(usize, usize)>>,
+}
+impl AbstractGraph {
+    fn new(grid: &Grid<Cell>, cluster_size: usize, stats: &mut SearchStats) -> AbstractGraph {
+        let mut graph = AbstractGraph { cluster_size, nodes: Vec::new(), index: HashMap::new(), edges: Vec::new() };
+        let is_open = |coordinate: Coordinate| grid[coordinate].cell_type != CellType::Wall;
+        let step_cost = |from: Coordinate, to: Coordinate| Connectivity::Four.step_cost_between(grid, from, to) * grid[to].weight;
+
+        // The borders between each cluster and the ones to its right and below it
+        // Along each, every unbroken stretch of cells open on both sides becomes one crossing in its middle, or two at its ends
+        // if it's long, so a route along the border doesn't have to detour far to get across it
+        let mut crossings = Vec::new();
+        for (vertical, border_count, border_length) in [(true, grid.width, grid.height), (false, grid.height, grid.width)] {
+            for near in (cluster_size - 1..border_count - 1).step_by(cluster_size) {
+                let pair = |along: usize| {
+                    if vertical {
+                        (Coordinate::new(near, along), Coordinate::new(near + 1, along))
+                    }
+                    else {
+                        (Coordinate::new(along, near), Coordinate::new(along, near + 1))
+                    }
+                };
+                for cluster_start in (0..border_length).step_by(cluster_size) {
+                    let cluster_end = (cluster_start + cluster_size).min(border_length);
+                    let mut along = cluster_start;
+                    while along < cluster_end {
+                        let stretch_start = along;
+                        while along < cluster_end && is_open(pair(along).0) && is_open(pair(along).1) {
+                            along += 1;
+                        }
+                        match along - stretch_start {
+                            0 => along += 1,
+                            length if length >= 6 => crossings.extend([pair(stretch_start), pair(along - 1)]),
+                            length => crossings.push(pair(stretch_start + length / 2)),
+                        }
+                    }
+                }
+            }
+        }
+        for (near, far) in crossings {
+            let (near_node, far_node) = (graph.add_node(near), graph.add_node(far));
+            graph.edges[near_node].push((far_node, step_cost(near, far)));
+            graph.edges[far_node].push((near_node, step_cost(far, near)));
+        }
+        graph.add_node(grid.entrance_location);
+        for &exit in &grid.exits {
+            graph.add_node(exit);
+        }
+
+        // Join every node to the others in its cluster that it can reach without leaving the cluster
+        let mut clusters: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (node, &coordinate) in graph.nodes.iter().enumerate() {
+            clusters.entry(graph.cluster_of(coordinate)).or_default().push(node);
+        }
+        for members in clusters.values() {
+            for &node in members {
+                let costs = graph.search_cluster(grid, graph.nodes[node], stats);
+                for &other in members.iter().filter(|&&other| other != node) {
+                    if let Some(&(cost, _)) = costs.get(&graph.nodes[other]) {
+                        graph.edges[node].push((other, cost));
+                    }
+                }
+            }
+        }
+        graph
+    }
+
+    fn add_node(&mut self, coordinate: Coordinate) -> usize {
+        *self.index.entry(coordinate).or_insert_with(|| {
+            self.nodes.push(coordinate);
+            self.edges.push(Vec::new());
+            self.nodes.len() - 1
+        })
+    }
+
+    // Which cluster a coordinate is in, as (column, row) of clusters
+    fn cluster_of(&self, coordinate: Coordinate) -> (usize, usize) {
+        (coordinate.x as usize / self.cluster_size, coordinate.y as usize / self.cluster_size)
+    }
+
+    // Dijkstra from start to every cell of its cluster it can reach without leaving it, giving each cell's cost and parent
+    fn search_cluster(&self, grid: &Grid<Cell>, start: Coordinate, stats: &mut SearchStats) -> HashMap<Coordinate, (usize, Coordinate)> {
+        let cluster = self.cluster_of(start);
+        let mut costs = HashMap::from([(start, (0, start))]);
+        let mut open_set = BinaryHeap::from([Reverse((0, start.y, start.x))]);
+        while let Some(Reverse((cost, y, x))) = open_set.pop() {
+            let current = Coordinate{x, y};
+            if cost > costs[&current].0 {
+                continue;
+            }
+            stats.nodes_expanded += 1;
+            for neighbour in grid.neighbours(current, Connectivity::Four) {
+                if self.cluster_of(neighbour) != cluster || grid[neighbour].cell_type == CellType::Wall {
+                    continue;
+                }
+                let tentative_cost = cost + Connectivity::Four.step_cost_between(grid, current, neighbour) * grid[neighbour].weight;
+                if costs.get(&neighbour).is_some_and(|&(best_cost, _)| tentative_cost >= best_cost) {
+                    continue;
+                }
+                costs.insert(neighbour, (tentative_cost, current));
+                open_set.push(Reverse((tentative_cost, neighbour.y, neighbour.x)));
+            }
+        }
+        costs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_maze;
+
+    // How much dearer than the shortest path an HPA* path comes out, for the mazes and cluster size below
+    fn overhead(name: &str, cluster_size: usize) -> f64 {
+        overhead_on(load_maze(name), cluster_size, name)
+    }
+
+    fn overhead_on(grid: Grid<Cell>, cluster_size: usize, name: &str) -> f64 {
+        let (astar, _) = solve_with(&mut grid.clone(), Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, None);
+        let (hpa, _) = solve_hpa(&mut grid.clone(), cluster_size);
+        let (astar, hpa) = (astar.unwrap(), hpa.unwrap());
+        assert_eq!(grid.validate_path(&hpa.path, Connectivity::Four), Ok(hpa.cost), "{}", name);
+        assert!(hpa.cost >= astar.cost, "{}", name);
+        hpa.cost as f64 / astar.cost as f64
+    }
+
+    #[test]
+    fn paths_are_near_optimal() {
+        for name in ["maze-Medium", "maze-Large"] {
+            let overhead = overhead(name, 16);
+            assert!(overhead <= 1.1, "{}'s HPA* path costs {:.3} times the shortest", name, overhead);
+        }
+    }
+
+    // Big open maps are what HPA* is for, and with no corridors to follow it's the border cells alone that steer the route
+    #[test]
+    fn paths_across_an_open_room_are_near_optimal() {
+        let size = 100;
+        let rows: Vec<String> = (0..size).map(|y| (0..size).map(|x| {
+            let border = x == 0 || y == 0 || x == size - 1 || y == size - 1;
+            if !border || (x, y) == (0, 1) || (x, y) == (size - 1, size - 2) { '-' } else { '#' }
+        }).collect()).collect();
+        let overhead = overhead_on(rows.join("\n").parse().unwrap(), 16, "open room");
+        assert!(overhead <= 1.1, "the HPA* path costs {:.3} times the shortest", overhead);
+    }
+
+    // Too few clusters to be worth it, so it's plain A* and the path is the shortest
+    #[test]
+    fn small_mazes_fall_back_to_astar() {
+        assert_eq!(overhead("maze-Easy", 16), 1.0);
+    }
+}
@@ -0,0 +1,124 @@
+use std::{cmp::Reverse, collections::HashSet, time::Instant};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, solve::{Connectivity, Heuristic, SearchError, SearchLimits, SearchStats, SearchStep, Solution}};
+
+// Iterative-deepening A* (IDA*): a depth-first search that gives up on any route whose f-score (cost so far plus the
+// heuristic) goes over a bound, starting the bound at the entrance's heuristic and raising it to the smallest f-score that
+// went over each time round until an exit is reached within it
+// It only ever holds the route it's currently on, and the neighbours still to try along it, so its memory grows with the
+// length of the path rather than the size of the maze; with an admissible heuristic the first exit it reaches is as cheap
+// as the one A* finds. The price is time: every round searches again from the entrance, and with nothing remembering which
+// cells have been seen, a maze with many loops gets its routes round them walked again and again
+// stats.max_frontier_size counts the neighbours waiting to be tried along the current route
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn solve_ida_star(grid: &Grid<Cell>, start: Coordinate, goals: &[Coordinate], blocked: &HashSet<Coordinate>, connectivity: Connectivity,
+                             heuristic: Heuristic, limits: SearchLimits, mut on_step: Option<&mut dyn FnMut(&SearchStep)>)
+                             -> Result<(Option<Solution>, SearchStats), SearchError> {
+    let start_time = Instant::now();
+    let mut stats = SearchStats::default();
+    let estimate = |coordinate: Coordinate| heuristic.estimate_nearest(grid, coordinate, goals, connectivity);
+    // A cell's neighbours, ordered so the one that looks closest to an exit gets popped, and so tried, first
+    let neighbours_of = |coordinate: Coordinate| {
+        let mut neighbours = grid.neighbours(coordinate, connectivity);
+        neighbours.sort_by_key(|&neighbour| Reverse(estimate(neighbour)));
+        neighbours
+    };
+    if goals.contains(&start) {
+        stats.path_length = 1;
+        stats.elapsed = start_time.elapsed();
+        return Ok((Some(Solution { path: vec![start], exit: start, cost: 0 }), stats));
+    }
+
+    let mut bound = estimate(start);
+    loop {
+        // The route so far, what it costs to get to each cell on it, and the neighbours of each still to be tried
+        let mut path = vec![start];
+        let mut costs = vec![0];
+        let mut on_path = HashSet::from([start]);
+        limits.check(&stats, start_time)?;
+        stats.nodes_expanded += 1;
+        let mut untried = vec![neighbours_of(start)];
+        let mut frontier_size = untried[0].len();
+        // The smallest f-score that went over the bound this round, which becomes the next bound
+        let mut next_bound: Option<usize> = None;
+        while let Some(neighbours) = untried.last_mut() {
+            let Some(neighbour) = neighbours.pop() else {
+                // Everything onwards from the end of the route has been tried, so back up a cell
+                untried.pop();
+                costs.pop();
+                on_path.remove(&path.pop().unwrap());
+                continue;
+            };
+            frontier_size -= 1;
+            let current = *path.last().unwrap();
+            // Going back to a cell already on the route can never lead anywhere cheaper
+            if grid[neighbour].cell_type == CellType::Wall || grid.step_blocked(current, neighbour, blocked) || on_path.contains(&neighbour) {
+                continue;
+            }
+            let cost = costs.last().unwrap() + connectivity.step_cost_between(grid, current, neighbour) * grid[neighbour].weight;
+            let f_score = cost + estimate(neighbour);
+            if f_score > bound {
+                next_bound = Some(next_bound.map_or(f_score, |next_bound| next_bound.min(f_score)));
+                continue;
+            }
+            path.push(neighbour);
+            costs.push(cost);
+            if goals.contains(&neighbour) {
+                stats.path_length = path.len();
+                stats.elapsed = start_time.elapsed();
+                return Ok((Some(Solution { path, exit: neighbour, cost }), stats));
+            }
+            on_path.insert(neighbour);
+            limits.check(&stats, start_time)?;
+            stats.nodes_expanded += 1;
+            untried.push(neighbours_of(neighbour));
+            frontier_size += untried.last().unwrap().len();
+            stats.max_frontier_size = stats.max_frontier_size.max(frontier_size);
+            if let Some(on_step) = on_step.as_mut() {
+                on_step(&SearchStep { expanded: neighbour, frontier: untried.iter().flatten().copied().collect() });
+            }
+        }
+        // Nothing went over the bound, so the whole of the reachable maze was searched without finding an exit
+        let Some(next_bound) = next_bound else {
+            stats.elapsed = start_time.elapsed();
+            return Ok((None, stats));
+        };
+        bound = next_bound;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{grid::{Cell, Grid}, load_maze, solve::{solve_with, Algorithm, Connectivity, Heuristic}};
+
+    fn solve_both(name: &str) -> (usize, usize, usize, usize) {
+        let grid = load_maze(name);
+        let (astar, astar_stats) = solve_with(&mut grid.clone(), Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, None);
+        let (ida_star, ida_star_stats) = solve_with(&mut grid.clone(), Algorithm::IdaStar, Connectivity::Four, Heuristic::Manhattan, None);
+        let (astar, ida_star) = (astar.unwrap(), ida_star.unwrap());
+        assert_eq!(grid.validate_path(&ida_star.path, Connectivity::Four), Ok(ida_star.cost), "{}", name);
+        (astar.cost, ida_star.cost, astar_stats.nodes_expanded, ida_star_stats.max_frontier_size)
+    }
+
+    #[test]
+    fn matches_astar_on_maze_easy() {
+        let (astar_cost, ida_star_cost, _, _) = solve_both("maze-Easy");
+        assert_eq!(ida_star_cost, astar_cost);
+    }
+
+    // A* keeps a cost and a parent for every cell it expands; IDA* only holds the route it's on and the neighbours along it
+    // still to try, which on maze-Medium is well under half as many
+    #[test]
+    fn matches_astar_on_maze_medium_holding_fewer_cells() {
+        let (astar_cost, ida_star_cost, astar_expanded, ida_star_held) = solve_both("maze-Medium");
+        assert_eq!(ida_star_cost, astar_cost);
+        assert!(ida_star_held * 2 < astar_expanded, "IDA* held {} cells at once, and A* expanded {}", ida_star_held, astar_expanded);
+    }
+
+    #[test]
+    fn a_sealed_exit_is_unreachable() {
+        let grid: Grid<Cell> = "#####\n--#--\n#####".parse().unwrap();
+        let (solution, _) = solve_with(&mut grid.clone(), Algorithm::IdaStar, Connectivity::Four, Heuristic::Manhattan, None);
+        assert_eq!(solution, None);
+    }
+}
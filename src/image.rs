@@ -0,0 +1,85 @@
+use std::{collections::HashSet, fs::File, io::{self, BufWriter, Write}, path::Path};
+
+use crate::grid::{Cell, CellType, Coordinate, Grid};
+
+const WALL_COLOUR: [u8; 3] = [0, 0, 0];
+const OPEN_COLOUR: [u8; 3] = [255, 255, 255];
+const PATH_COLOUR: [u8; 3] = [220, 30, 30];
+const ENTRANCE_COLOUR: [u8; 3] = [30, 180, 30];
+const EXIT_COLOUR: [u8; 3] = [30, 60, 220];
+
+// Write the maze and its solution to a PNG at out, with every cell drawn as a scale x scale block of pixels
+// Only one row of pixels is held in memory at a time, so even maze-VLarge.txt at a large scale is fine
+pub fn save_png(grid: &Grid<Cell>, path: &[Coordinate], scale: u32, out: &Path) -> io::Result<()> {
+    let scale = scale.max(1) as usize;
+    let path_cells: HashSet<&Coordinate> = path.iter().collect();
+    let image_width = u32::try_from(grid.width * scale).map_err(io::Error::other)?;
+    let image_height = u32::try_from(grid.height * scale).map_err(io::Error::other)?;
+
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(out)?), image_width, image_height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    let mut stream = writer.stream_writer().map_err(io::Error::other)?;
+
+    let mut pixel_row = Vec::with_capacity(grid.width * scale * 3);
+    for row in grid.cells.chunks(grid.width) {
+        pixel_row.clear();
+        for cell in row {
+            let colour = if cell.coordinate == grid.entrance_location {
+                ENTRANCE_COLOUR
+            }
+            else if grid.exits.contains(&cell.coordinate) {
+                EXIT_COLOUR
+            }
+            else if path_cells.contains(&cell.coordinate) {
+                PATH_COLOUR
+            }
+            else if cell.cell_type == CellType::Wall {
+                WALL_COLOUR
+            }
+            else {
+                OPEN_COLOUR
+            };
+            for _ in 0..scale {
+                pixel_row.extend_from_slice(&colour);
+            }
+        }
+        // Each row of cells is scale pixels tall, so write the same row of pixels that many times
+        for _ in 0..scale {
+            stream.write_all(&pixel_row)?;
+        }
+    }
+    stream.finish().map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io::BufReader, process};
+
+    use super::*;
+    use crate::solve::{solve, SolveOutcome};
+
+    #[test]
+    fn each_cell_is_a_scaled_block_in_its_colour() {
+        let mut grid: Grid<Cell> = "#####\n--#-#\n#---#\n###--\n#####".parse().unwrap();
+        let SolveOutcome::Found { path, .. } = solve(&mut grid) else {
+            panic!("no path through the maze");
+        };
+        let out = env::temp_dir().join(format!("mazesolver-image-{}.png", process::id()));
+        save_png(&grid, &path, 3, &out).unwrap();
+        let mut reader = png::Decoder::new(BufReader::new(File::open(&out).unwrap())).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        fs::remove_file(&out).unwrap();
+        assert_eq!((info.width, info.height), (5 * 3, 5 * 3));
+        // The colour of the pixel at (x, y), three bytes a pixel
+        let pixel = |x: usize, y: usize| &pixels[(y * info.width as usize + x) * 3..][..3];
+        // Every pixel of a cell's block is the same colour, so check the corners of a few; the dead end at (3, 1) is off the path
+        for (x, y, colour) in [(0, 0, WALL_COLOUR), (0, 1, ENTRANCE_COLOUR), (1, 1, PATH_COLOUR), (2, 2, PATH_COLOUR), (3, 1, OPEN_COLOUR), (3, 3, PATH_COLOUR),
+                               (4, 3, EXIT_COLOUR)] {
+            assert_eq!(pixel(x * 3, y * 3), colour, "cell ({}, {})", x, y);
+            assert_eq!(pixel(x * 3 + 2, y * 3 + 2), colour, "cell ({}, {})", x, y);
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use std::{collections::{BinaryHeap, HashMap}, time::Instant};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, solve::{Connectivity, Heuristic, SearchError, SearchLimits, SearchStats, SearchStep, Solution}};
+
+// Jump Point Search only finds shortest paths when every step in a straight line costs the same and the only way
+// between cells is stepping across the grid, so it can't be used on weighted terrain, with portals, or on a maze that wraps round
+pub(crate) fn is_uniform_cost(grid: &Grid<Cell>) -> bool {
+    grid.cost_x == grid.cost_y && !grid.toroidal && grid.portals.is_empty() && !grid.has_one_way_cells()
+        && grid.cells.iter().all(|cell| cell.cell_type == CellType::Wall || cell.weight == 1)
+}
+
+// Whether (x, y) is inside the grid and not a wall
+// Jumps step off the edges of the grid, so coordinates here are signed and anything negative is simply not walkable
+fn walkable(grid: &Grid<Cell>, x: isize, y: isize) -> bool {
+    x >= 0 && y >= 0 && grid.try_cell_at(Coordinate::new(x as usize, y as usize)).is_some_and(|cell| cell.cell_type != CellType::Wall)
+}
+
+// Walk from (x, y) in the direction (dx, dy) until we hit something worth stopping at: an exit, or a jump point,
+// a cell with a neighbour that can't be reached more cheaply any other way (a "forced" neighbour) than through it
+// Every cell passed over on the way has only one sensible way onwards, so none of them need to go in the open set
+// Returns None if the walk runs into a wall or off the grid without finding anywhere to stop
+fn jump(grid: &Grid<Cell>, goals: &[Coordinate], connectivity: Connectivity, mut x: isize, mut y: isize, dx: isize, dy: isize) -> Option<Coordinate> {
+    loop {
+        if !walkable(grid, x, y) {
+            return None;
+        }
+        let here = Coordinate::new(x as usize, y as usize);
+        if goals.contains(&here) {
+            return Some(here);
+        }
+        if dx != 0 && dy != 0 {
+            // Moving diagonally, stop here if either of the straight lines leading off it finds a jump point
+            if jump(grid, goals, connectivity, x + dx, y, dx, 0).is_some() || jump(grid, goals, connectivity, x, y + dy, 0, dy).is_some() {
+                return Some(here);
+            }
+            // We never cut corners, so the diagonal can only carry on if both the cells beside the next step are open
+            if !(walkable(grid, x + dx, y) && walkable(grid, x, y + dy)) {
+                return None;
+            }
+        }
+        else if dx != 0 {
+            // Moving horizontally, a wall behind us above or below that opens up beside us means a forced neighbour
+            if (walkable(grid, x, y - 1) && !walkable(grid, x - dx, y - 1)) || (walkable(grid, x, y + 1) && !walkable(grid, x - dx, y + 1)) {
+                return Some(here);
+            }
+        }
+        else {
+            // Likewise moving vertically, with the walls to our left and right
+            if (walkable(grid, x - 1, y) && !walkable(grid, x - 1, y - dy)) || (walkable(grid, x + 1, y) && !walkable(grid, x + 1, y - dy)) {
+                return Some(here);
+            }
+            // Without diagonals, turning a corner means stopping, so vertical jumps have to look sideways for jump points too
+            if connectivity == Connectivity::Four && (jump(grid, goals, connectivity, x + 1, y, 1, 0).is_some() || jump(grid, goals, connectivity, x - 1, y, -1, 0).is_some()) {
+                return Some(here);
+            }
+        }
+        x += dx;
+        y += dy;
+    }
+}
+
+// The directions worth jumping in from coordinate, given the jump point we reached it from
+// Anything behind us could have been reached at least as cheaply without passing through here, so it's pruned
+fn directions(grid: &Grid<Cell>, connectivity: Connectivity, coordinate: Coordinate, parent: Option<Coordinate>) -> Vec<(isize, isize)> {
+    let (x, y) = (coordinate.x as isize, coordinate.y as isize);
+    // The entrance has no parent, so nothing can be pruned and we head off towards every neighbour
+    let Some(parent) = parent else {
+        return grid.neighbours(coordinate, connectivity).into_iter()
+            .map(|neighbour| (neighbour.x as isize - x, neighbour.y as isize - y))
+            .collect();
+    };
+    let dx = (x - parent.x as isize).signum();
+    let dy = (y - parent.y as isize).signum();
+    let mut directions = Vec::new();
+    match connectivity {
+        Connectivity::Four => {
+            if dx != 0 {
+                directions.extend([(dx, 0), (0, -1), (0, 1)]);
+            }
+            else {
+                directions.extend([(0, dy), (-1, 0), (1, 0)]);
+            }
+        },
+        Connectivity::Eight if dx != 0 && dy != 0 => {
+            let vertical_open = walkable(grid, x, y + dy);
+            let horizontal_open = walkable(grid, x + dx, y);
+            if vertical_open {
+                directions.push((0, dy));
+            }
+            if horizontal_open {
+                directions.push((dx, 0));
+            }
+            if vertical_open && horizontal_open {
+                directions.push((dx, dy));
+            }
+        },
+        Connectivity::Eight => {
+            // (ahead_x, ahead_y) is the way we were already going, and (side_x, side_y) is one of the two ways at right angles to it
+            let (ahead_x, ahead_y) = (dx, dy);
+            let ahead_open = walkable(grid, x + ahead_x, y + ahead_y);
+            if ahead_open {
+                directions.push((ahead_x, ahead_y));
+            }
+            for (side_x, side_y) in [(ahead_y, ahead_x), (-ahead_y, -ahead_x)] {
+                if walkable(grid, x + side_x, y + side_y) {
+                    directions.push((side_x, side_y));
+                    if ahead_open {
+                        directions.push((ahead_x + side_x, ahead_y + side_y));
+                    }
+                }
+            }
+        },
+    }
+    directions
+}
+
+// A* that only puts jump points in the open set, jumping straight over the cells between them
+// On big open rooms this expands a tiny fraction of the cells plain A* does, for a path of exactly the same length.
+// Callers should check is_uniform_cost first; on weighted terrain the paths it finds may not be the shortest
+// Like solve_with it searches from start to whichever of goals it reaches first
+pub(crate) fn solve_jps(grid: &Grid<Cell>, start: Coordinate, goals: &[Coordinate], connectivity: Connectivity, heuristic: Heuristic,
+                        limits: SearchLimits, mut on_step: Option<&mut dyn FnMut(&SearchStep)>) -> Result<(Option<Solution>, SearchStats), SearchError> {
+    let start_time = Instant::now();
+    let mut stats = SearchStats::default();
+    let mut open_set = BinaryHeap::new();
+    let mut best_costs = HashMap::new();
+    // The jump point each jump point was reached from; the cells in between are filled back in once we've found an exit
+    let mut parents: HashMap<Coordinate, Coordinate> = HashMap::new();
+    // Jump points are joined by straight or diagonal lines through open cells, so the distance along them is exactly
+    // the default heuristic for the connectivity
+    let distance = Heuristic::default_for(connectivity);
+    let mut exit_reached = None;
+
+    let mut start_cell = grid[start];
+    start_cell.cost = 0;
+    start_cell.f_score = 0;
+    best_costs.insert(start, 0);
+    open_set.push(start_cell);
+    while let Some(current_cell) = open_set.pop() {
+        if current_cell.cost > best_costs[&current_cell.coordinate] {
+            continue;
+        }
+        if goals.contains(&current_cell.coordinate) {
+            exit_reached = Some(current_cell.coordinate);
+            break;
+        }
+        limits.check(&stats, start_time)?;
+        stats.nodes_expanded += 1;
+        let Coordinate { x, y } = current_cell.coordinate;
+        let parent = parents.get(&current_cell.coordinate).copied();
+        for (dx, dy) in directions(grid, connectivity, current_cell.coordinate, parent) {
+            let Some(jump_point) = jump(grid, goals, connectivity, x as isize + dx, y as isize + dy, dx, dy) else {
+                continue;
+            };
+            let tentative_cost = current_cell.cost + distance.estimate(grid, current_cell.coordinate, jump_point, connectivity);
+            if best_costs.get(&jump_point).is_some_and(|&best_cost| tentative_cost >= best_cost) {
+                continue;
+            }
+            best_costs.insert(jump_point, tentative_cost);
+            parents.insert(jump_point, current_cell.coordinate);
+            let mut jump_cell = grid[jump_point];
+            jump_cell.cost = tentative_cost;
+            jump_cell.h = heuristic.estimate_nearest(grid, jump_point, goals, connectivity);
+            jump_cell.f_score = jump_cell.cost + jump_cell.h;
+            open_set.push(jump_cell);
+        }
+        stats.max_frontier_size = stats.max_frontier_size.max(open_set.len());
+        if let Some(on_step) = on_step.as_mut() {
+            on_step(&SearchStep { expanded: current_cell.coordinate, frontier: open_set.iter().map(|cell| cell.coordinate).collect() });
+        }
+    }
+
+    let Some(exit) = exit_reached else {
+        stats.elapsed = start_time.elapsed();
+        return Ok((None, stats));
+    };
+    // Backtrack through the jump points, filling in every cell along the line between each one and its parent
+    let mut path = vec![exit];
+    let mut current = exit;
+    while let Some(&parent) = parents.get(&current) {
+        let dx = (parent.x as isize - current.x as isize).signum();
+        let dy = (parent.y as isize - current.y as isize).signum();
+        while current != parent {
+            current = Coordinate::new((current.x as isize + dx) as usize, (current.y as isize + dy) as usize);
+            path.push(current);
+        }
+    }
+    path.reverse();
+    stats.path_length = path.len();
+    stats.elapsed = start_time.elapsed();
+    Ok((Some(Solution { path, exit, cost: best_costs[&exit] }), stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{grid::{Cell, Grid}, solve::{solve_with, Algorithm, Connectivity, Heuristic}};
+
+    // A 30x30 room with a wall coming down most of the way through the middle of it, so the way round has to be found
+    fn room() -> Grid<Cell> {
+        let size = 30;
+        let rows: Vec<String> = (0..size).map(|y| (0..size).map(|x| {
+            let opening = (x, y) == (0, 1) || (x, y) == (size - 1, size - 2);
+            let wall = x == 0 || y == 0 || x == size - 1 || y == size - 1 || (x == size / 2 && y < size - 4);
+            if opening || !wall { '-' } else { '#' }
+        }).collect()).collect();
+        rows.join("\n").parse().unwrap()
+    }
+
+    #[test]
+    fn jps_finds_a_path_as_short_as_astar_expanding_far_fewer_cells() {
+        let grid = room();
+        for (connectivity, heuristic) in [(Connectivity::Four, Heuristic::Manhattan), (Connectivity::Eight, Heuristic::Octile)] {
+            let (astar, astar_stats) = solve_with(&mut grid.clone(), Algorithm::AStar, connectivity, heuristic, None);
+            let (jps, jps_stats) = solve_with(&mut grid.clone(), Algorithm::Jps, connectivity, heuristic, None);
+            let (astar, jps) = (astar.unwrap(), jps.unwrap());
+            assert_eq!(jps.cost, astar.cost, "{:?}", connectivity);
+            assert_eq!(grid.validate_path(&jps.path, connectivity), Ok(jps.cost), "{:?}", connectivity);
+            assert!(jps_stats.nodes_expanded * 10 <= astar_stats.nodes_expanded,
+                    "{:?}: JPS expanded {} cells and A* {}", connectivity, jps_stats.nodes_expanded, astar_stats.nodes_expanded);
+        }
+    }
+
+    // Weighted terrain breaks the jumps' assumption that every step costs the same, so JPS has to fall back to A*
+    #[test]
+    fn jps_falls_back_to_astar_on_weighted_terrain() {
+        let grid: Grid<Cell> = "#######\n#-----#\n--999--\n#-----#\n#######".parse().unwrap();
+        let (astar, astar_stats) = solve_with(&mut grid.clone(), Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, None);
+        let (jps, jps_stats) = solve_with(&mut grid.clone(), Algorithm::Jps, Connectivity::Four, Heuristic::Manhattan, None);
+        assert_eq!(jps, astar);
+        assert_eq!(jps_stats.nodes_expanded, astar_stats.nodes_expanded);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{analyze::MazeAnalysis, grid::{Cell, Coordinate, Grid}, solve::Solution};
+
+// Write a coordinate as a JSON [x, y] pair
+fn coordinate_to_json(coordinate: Coordinate) -> String {
+    format!("[{},{}]", coordinate.x, coordinate.y)
+}
+
+// Describe the maze and its solution as a single line of JSON, for tools outside Rust:
+// {"width":W,"height":H,"entrance":[x,y],"exit":[x,y],"path":[[x,y],...],"length":N}
+// Coordinates are [x, y] with x counting columns from the left and y counting rows from the top.
+// If there's no solution, exit and path are null and length is 0
+pub fn solution_to_json(grid: &Grid<Cell>, solution: Option<&Solution>) -> String {
+    let (exit, path, length) = match solution {
+        Some(solution) => {
+            let path: Vec<String> = solution.path.iter().map(|&coordinate| coordinate_to_json(coordinate)).collect();
+            (coordinate_to_json(solution.exit), format!("[{}]", path.join(",")), solution.path.len())
+        },
+        None => ("null".to_string(), "null".to_string(), 0),
+    };
+    format!(
+        "{{\"width\":{},\"height\":{},\"entrance\":{},\"exit\":{},\"path\":{},\"length\":{}}}",
+        grid.width,
+        grid.height,
+        coordinate_to_json(grid.entrance_location),
+        exit,
+        path,
+        length,
+    )
+}
+
+// Describe a maze analysis as a single line of JSON:
+// {"width":W,"height":H,"walls":N,"open_cells":N,"openings":N,"perfect":true,"dead_ends":N,"junctions":N,"entrance":[x,y],"exits":[[x,y],...]}
+pub fn analysis_to_json(analysis: &MazeAnalysis) -> String {
+    let exits: Vec<String> = analysis.exits.iter().map(|&coordinate| coordinate_to_json(coordinate)).collect();
+    format!(
+        "{{\"width\":{},\"height\":{},\"walls\":{},\"open_cells\":{},\"openings\":{},\"perfect\":{},\"dead_ends\":{},\"junctions\":{},\"entrance\":{},\"exits\":[{}]}}",
+        analysis.width,
+        analysis.height,
+        analysis.walls,
+        analysis.open_cells,
+        analysis.openings,
+        analysis.perfect,
+        analysis.dead_ends,
+        analysis.junctions,
+        coordinate_to_json(analysis.entrance),
+        exits.join(","),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_maze, solve::{solve_with, Algorithm, Connectivity, Heuristic}};
+
+    // The text of a field's value: up to the comma or brace after it, or for a list, the bracket that closes it
+    fn field<'a>(json: &'a str, name: &str) -> &'a str {
+        let start = json.find(&format!("\"{}\":", name)).unwrap_or_else(|| panic!("no {} in {}", name, json)) + name.len() + 3;
+        let rest = &json[start..];
+        let end = if rest.starts_with("[[") {
+            rest.find("]]").unwrap() + 2
+        }
+        else if rest.starts_with('[') {
+            rest.find(']').unwrap() + 1
+        }
+        else {
+            rest.find([',', '}']).unwrap()
+        };
+        &rest[..end]
+    }
+
+    // Read back a list of [x,y] pairs, or a single pair
+    fn coordinates(text: &str) -> Vec<Coordinate> {
+        text.split("],[").map(|pair| {
+            let (x, y) = pair.trim_matches(['[', ']']).split_once(',').unwrap();
+            Coordinate { x: x.parse().unwrap(), y: y.parse().unwrap() }
+        }).collect()
+    }
+
+    #[test]
+    fn the_path_round_trips_through_json() {
+        let mut grid = load_maze("maze-Medium");
+        let (solution, _) = solve_with(&mut grid, Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, None);
+        let solution = solution.unwrap();
+        let json = solution_to_json(&grid, Some(&solution));
+        assert_eq!(coordinates(field(&json, "path")), solution.path);
+        assert_eq!(coordinates(field(&json, "entrance")), [grid.entrance()]);
+        assert_eq!(coordinates(field(&json, "exit")), [solution.exit]);
+        assert_eq!(field(&json, "length"), "321");
+        assert_eq!((field(&json, "width"), field(&json, "height")), ("200", "100"));
+    }
+
+    #[test]
+    fn no_solution_is_null() {
+        let grid = load_maze("maze-Easy");
+        let json = solution_to_json(&grid, None);
+        assert_eq!((field(&json, "exit"), field(&json, "path"), field(&json, "length")), ("null", "null", "0"));
+    }
+}
@@ -0,0 +1,62 @@
+pub mod all_shortest;
+pub mod analyze;
+pub mod bidirectional;
+pub mod binary;
+pub mod bitgrid;
+pub mod contract;
+pub mod custom;
+pub mod distance_field;
+pub mod dot;
+pub mod format;
+pub mod generate;
+pub mod grid;
+pub mod hpa;
+pub mod ida_star;
+#[cfg(feature = "image")]
+pub mod image;
+pub mod jps;
+pub mod json;
+pub mod multi;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod parse;
+pub mod render;
+pub mod solve;
+pub mod solver;
+pub mod svg;
+pub mod theta_star;
+pub mod turn_penalty;
+pub mod validate;
+
+pub use all_shortest::solve_all_shortest;
+pub use analyze::{analyze, MazeAnalysis};
+pub use bidirectional::solve_bidirectional;
+pub use bitgrid::BitGrid;
+pub use contract::solve_contracted;
+pub use custom::solve_with_costs;
+pub use distance_field::{distance_field, optimal_cells};
+pub use format::Format;
+pub use generate::{generate, generate_text};
+pub use grid::{Cell, CellType, Coordinate, Direction, Grid, NeighbourOrder, MAX_SIDE};
+pub use hpa::solve_hpa;
+#[cfg(feature = "image")]
+pub use image::save_png;
+pub use json::{analysis_to_json, solution_to_json};
+pub use multi::solve_multi;
+#[cfg(feature = "parallel")]
+pub use parallel::solve_parallel;
+pub use parse::{GridConfig, MazeError};
+pub use render::{render, render_colored, render_heatmap, render_numbered, render_search, render_visited};
+pub use solve::{path_to_directions, solve, solve_avoiding, solve_best_effort, solve_bfs, solve_between, solve_cost_with_limits, solve_dfs, solve_with, solve_with_limits, solve_with_max_visited, Algorithm, Connectivity, Heuristic, SearchError, SearchLimits, SearchStats, SearchStep, Solution, SolveOutcome};
+pub use solver::MazeSolver;
+pub use svg::to_svg;
+pub use theta_star::{euclidean_length, line_of_sight, solve_theta_star};
+pub use turn_penalty::solve_with_turn_penalty;
+pub use validate::PathError;
+
+// One of the bundled mazes in mazes/, for the tests
+#[cfg(test)]
+pub(crate) fn load_maze(name: &str) -> Grid<Cell> {
+    let path = format!("{}/mazes/{}.txt", env!("CARGO_MANIFEST_DIR"), name);
+    Grid::new(std::path::Path::new(&path)).unwrap_or_else(|error| panic!("couldn't load {}: {}", path, error))
+}
@@ -1,203 +1,979 @@
-use std::{path::Path, fs::read_to_string, collections::{BinaryHeap, HashSet}};
-
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-struct Coordinate {
-    x: usize,
-    y: usize,
-}
-
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-enum CellType {
-    Entrance,
-    Exit,
-    Wall,
-    Path,
-}
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-struct Cell {
-    cell_type: CellType,
-    coordinate: Coordinate,
-    parent_coord: Coordinate,
-    manhattan_from_exit: usize,
-    cost: usize,
-}
-// define ordering so that we can use Cells in a BinaryHeap
-impl Ord for Cell {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.cost.cmp(&other.cost).reverse()
-    }
-}
-impl PartialOrd for Cell {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cost.cmp(&other.cost).reverse())
-    }
-}
-impl Cell {
-    // define a constructor for a Cell
-    fn new(coordinate: Coordinate, cell_type: CellType) -> Cell {
-        Cell {
-            cell_type,
-            coordinate,
-            parent_coord: Coordinate{x: 0, y: 0}, // set a default coordinate; (0, 0) is nearly always a wall, so we know if something goes wrong
-            manhattan_from_exit: 0,    
-            cost: 0,    // we leave cost at 0 so that if something goes wrong, the cost is still an underestimate and therefore
-                        // an admissible heuristic for A*
-        }
-    }
-}
-
-#[derive(Debug)]
-struct Grid<Cell> {
-    width: usize,
-    height: usize,
-    entrance_location: Coordinate,
-    exit_location: Coordinate,
-    cells: Vec<Cell>,
-}
-impl Grid<Cell> {
-    // Grid constructor
-    fn new(path_to_maze: &Path) -> Result<Grid<Cell>, std::io::Error> {
-        // Remove spaces from the maze we just read in
-        let maze_as_string = read_to_string(path_to_maze)?.replace(' ', "");
-        // Convert the maze to a vector of strings, one string per row
-        let maze_as_vec: Vec<&str> = maze_as_string.trim().lines().collect();
-        // Get the width and height of the maze
-        let width = maze_as_vec[0].len();
-        let height = maze_as_vec.len();
-        // Declare vectors to hold all the cells and a vector of coordinates to hold where the exits are
-        let mut cells = Vec::with_capacity(width * height);
-        let mut exit_coordinates = Vec::new();
-        // Boolean to check if we've found the entrance yet
-        let mut entrance_found = false;
-        for (row, chars) in maze_as_vec.iter().enumerate() {
-            for (column, char) in chars.chars().enumerate() {
-                match char {
-                    '-' => {
-                        if row == 0 || row == height - 1 || column == 0 || column == width - 1 {
-                            // Only the first '-' we find is the entrance, the rest are exits
-                            if !entrance_found {
-                                cells.push(Cell::new(Coordinate{x: column, y: row}, CellType::Entrance));
-                                entrance_found = true;
-                            }
-                            else {
-                                cells.push(Cell::new(Coordinate{x: column, y: row}, CellType::Exit));
-                            }
-                            exit_coordinates.push(Coordinate{x: column, y: row});
-                        }
-                        // If it's a '-' that's not on the edge, it's a path
-                        else {
-                            cells.push(Cell::new(Coordinate{x: column, y: row}, CellType::Path));
-                        }
-                    },
-                    // Any '#' is a wall
-                    '#' => {
-                        cells.push(Cell::new(Coordinate{x: column, y: row}, CellType::Wall));
-                    },
-                    _ => (),
+use std::{collections::HashSet, env, fs::{self, File}, io::{self, BufReader, IsTerminal, Write}, path::{Path, PathBuf}, process, thread, time::Duration};
+
+use log::{info, LevelFilter, Log, Metadata, Record};
+use mazesolver::{analysis_to_json, analyze, distance_field, euclidean_length, generate_text, optimal_cells, render, solve_bidirectional, path_to_directions, render_colored, render_heatmap, render_numbered, render_search, render_visited, solution_to_json, solve_best_effort, solve_contracted, solve_cost_with_limits, solve_hpa, solve_theta_star, solve_with_limits, solve_with_max_visited, solve_with_turn_penalty, to_svg, Algorithm, BitGrid, Cell, Connectivity, Coordinate, Format, Grid, GridConfig, Heuristic, MazeAnalysis, MazeError, NeighbourOrder, SearchError, SearchLimits, SearchStats, SearchStep, Solution, SolveOutcome};
+
+// How many cells --progress lets the search expand between status lines
+const PROGRESS_INTERVAL: usize = 10_000;
+
+// How many cells wide and tall each cluster is for --hpa
+const HPA_CLUSTER_SIZE: usize = 16;
+
+// What we've been asked to do: solve a maze from a file (or stdin), or generate a new one
+enum Mode {
+    Solve(String),
+    // Solve every maze file in a directory and print a table of how each went
+    Batch(String),
+    Generate { width: usize, height: usize, seed: u64 },
+}
+
+// The subcommands: mazesolver solve, render, analyze or generate, each taking only the options that make sense for it
+// Without one, the first argument is just the first option or the maze file, and every option is accepted as it always was
+#[derive(Copy, Clone, PartialEq)]
+enum Command {
+    Solve,
+    // Solve the maze and draw the path on it, or draw it as a heatmap or with its optimal cells marked
+    Render,
+    Analyze,
+    Generate,
+}
+impl Command {
+    fn from_name(name: &str) -> Option<Command> {
+        match name {
+            "solve" => Some(Command::Solve),
+            "render" => Some(Command::Render),
+            "analyze" => Some(Command::Analyze),
+            "generate" => Some(Command::Generate),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Command::Solve => "solve",
+            Command::Render => "render",
+            Command::Analyze => "analyze",
+            Command::Generate => "generate",
+        }
+    }
+
+    // How the command is run, for the usage message
+    fn usage(self) -> &'static str {
+        match self {
+            Command::Solve => "mazesolver solve [options] <maze file or ->",
+            Command::Render => "mazesolver render [options] <maze file or ->",
+            Command::Analyze => "mazesolver analyze [options] <maze file or ->",
+            Command::Generate => "mazesolver generate <width> <height> [--seed <n>]",
+        }
+    }
+
+    // Whether flag is one of this command's options
+    fn accepts(self, flag: &str) -> bool {
+        match self {
+            Command::Solve => !matches!(flag, "--generate" | "--seed" | "--analyze" | "--heatmap" | "--optimal-cells"),
+            Command::Render => MAZE_FLAGS.contains(&flag) || SEARCH_FLAGS.contains(&flag) || RENDER_FLAGS.contains(&flag),
+            Command::Analyze => MAZE_FLAGS.contains(&flag) || matches!(flag, "--low-memory" | "--output"),
+            Command::Generate => matches!(flag, "--seed" | "--quiet"),
+        }
+    }
+}
+
+// The options that say how to read a maze, which every command that reads one takes
+const MAZE_FLAGS: [&str; 11] = ["--format", "--comment-prefix", "--check-border", "--toroidal", "--open-field", "--entrance", "--exit", "--cost-x",
+                                "--cost-y", "--neighbour-order", "--quiet"];
+// The options that choose and limit the search
+const SEARCH_FLAGS: [&str; 13] = ["--algo", "--connectivity", "--heuristic", "--weight", "--bidirectional", "--turn-penalty", "--contract", "--hpa",
+                                  "--best-effort", "--max-visited", "--max-nodes", "--timeout", "--progress"];
+// The options that decide how the maze is drawn
+const RENDER_FLAGS: [&str; 11] = ["--no-color", "--numbered", "--visited", "--animate", "--fps", "--frames-dir", "--frame-every", "--heatmap",
+                                  "--optimal-cells", "--png", "--svg"];
+
+// Every option, as it's written in the usage message, with the flags it covers and what it does
+const OPTIONS: [(&[&str], &str, &str); 40] = [
+    (&["--algo"], "--algo astar|dijkstra|bfs|dfs|greedy|jps|idastar", "search algorithm (default astar)"),
+    (&["--connectivity"], "--connectivity 4|8", "allow diagonal moves with 8 (default 4)"),
+    (&["--heuristic"], "--heuristic manhattan|euclidean|chebyshev|octile|zero", "distance estimate for astar and greedy"),
+    (&["--weight"], "--weight <w>", "weighted A*: faster, with a path at most w times the shortest"),
+    (&["--cost-x", "--cost-y"], "--cost-x <n>, --cost-y <n>", "what a step across or up and down costs (default 1)"),
+    (&["--bidirectional"], "--bidirectional", "A* from both ends at once (ignores --algo)"),
+    (&["--turn-penalty"], "--turn-penalty <n>", "add n to the cost of every change of direction, for straighter paths"),
+    (&["--contract"], "--contract", "search junctions only, skipping along corridors (same path cost as A*)"),
+    (&["--hpa"], "--hpa", "hierarchical A*: near-optimal, but quicker on huge mazes"),
+    (&["--analyze"], "--analyze", "describe the maze without solving it"),
+    (&["--heatmap"], "--heatmap", "colour the maze by distance from the entrance (24-bit colour)"),
+    (&["--optimal-cells"], "--optimal-cells", "mark every cell on any shortest path, not just the one the search finds"),
+    (&["--best-effort"], "--best-effort", "if no exit can be reached, give the path to the cell closest to one"),
+    (&["--any-angle"], "--any-angle", "Theta*: print the turning points of an any-angle path"),
+    (&["--max-visited"], "--max-visited <n>", "stop after expanding this many cells, with the path to the closest cell reached"),
+    (&["--max-nodes"], "--max-nodes <n>", "give up after expanding this many cells"),
+    (&["--timeout"], "--timeout <ms>", "give up after this many milliseconds"),
+    (&["--output"], "--output text|json", "print the result as text or as JSON (default text)"),
+    (&["--render"], "--render", "print the maze with the path drawn on it"),
+    (&["--no-color"], "--no-color", "with --render, don't colour the maze (also set by NO_COLOR)"),
+    (&["--numbered"], "--numbered", "with --render, number the path's steps (modulo 10) in order"),
+    (&["--visited"], "--visited", "with --render, shade the cells the search visited"),
+    (&["--animate"], "--animate", "redraw the maze after every step of the search"),
+    (&["--fps"], "--fps <n>", "frames per second for --animate (default 30)"),
+    (&["--frames-dir"], "--frames-dir <directory>", "save the maze as text every step of the search, one numbered file each"),
+    (&["--frame-every"], "--frame-every <n>", "with --frames-dir, only save every nth step (default 1)"),
+    (&["--quiet"], "--quiet", "only log warnings and errors (RUST_LOG=debug for more)"),
+    (&["--progress"], "--progress", "show how the search is going while it runs"),
+    (&["--count-only"], "--count-only", "print the path's length and cost without building the path"),
+    (&["--low-memory"], "--low-memory", "with --analyze or --count-only, hold the maze as one bit a cell (count-only uses BFS)"),
+    (&["--directions"], "--directions", "print the path as compass directions"),
+    (&["--format"], "--format <ascii|json|csv|thinwall>", "the maze's format (normally worked out from its name or contents)"),
+    (&["--comment-prefix"], "--comment-prefix <prefix>", "skip maze lines starting with this, e.g. \"# \""),
+    (&["--toroidal"], "--toroidal", "the maze wraps round at its edges (pair with --entrance and --exit)"),
+    (&["--neighbour-order"], "--neighbour-order <NESW>", "the order to try each cell's neighbours in (default WENS)"),
+    (&["--open-field"], "--open-field", "the maze has no wall round it, so '-' on its edge isn't an opening (needs --entrance and --exit)"),
+    (&["--entrance", "--exit"], "--entrance <x,y>, --exit <x,y>", "start or finish here instead of at the maze's openings"),
+    (&["--check-border"], "--check-border", "reject mazes with gaps in the border that aren't openings"),
+    (&["--png"], "--png <output file>", "save the solved maze as a PNG (image feature)"),
+    (&["--svg"], "--svg <output file>", "save the solved maze as an SVG"),
+];
+
+// How to print the result of a solve
+#[derive(Copy, Clone, PartialEq)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+// Everything we read from the command line
+struct Options {
+    mode: Mode,
+    algorithm: Algorithm,
+    connectivity: Connectivity,
+    heuristic: Heuristic,
+    // What a step across the maze and a step up or down it cost
+    step_costs: (usize, usize),
+    bidirectional: bool,
+    // Solve with hierarchical A*, which is quicker on huge mazes but only near-optimal
+    hpa: bool,
+    // What a change of direction costs on top of the step itself, for paths that bend less
+    turn_penalty: Option<usize>,
+    // If no exit can be reached, give the path to the cell the heuristic puts closest to one instead
+    best_effort: bool,
+    // Stop after expanding this many cells, giving the path to the closest cell reached so far rather than an error
+    max_visited: Option<usize>,
+    // Solve over the maze's junctions and dead ends, with the corridors between them contracted into single edges
+    contract: bool,
+    // Report on the maze instead of solving it
+    analyze: bool,
+    // Print the maze coloured by how far each cell is from the entrance, instead of solving it
+    heatmap: bool,
+    // Print the maze with every cell on some shortest path marked, instead of solving it
+    optimal_cells: bool,
+    any_angle: bool,
+    limits: SearchLimits,
+    output: OutputFormat,
+    render: bool,
+    // With --render, number the path cells in the order they're walked instead of marking them all the same
+    numbered: bool,
+    no_color: bool,
+    show_visited: bool,
+    // Redraw the maze after every step of the search, this many times a second
+    animate_fps: Option<u32>,
+    // Write a snapshot of the search into this directory every frame_every cells expanded, one numbered file each
+    frames_dir: Option<PathBuf>,
+    frame_every: usize,
+    // Leave out the messages about how things are going, keeping only warnings and errors
+    quiet: bool,
+    // Print a status line on stderr as the search goes, so big mazes don't look like they've hung
+    progress: bool,
+    // Report what the path costs without ever building it
+    count_only: bool,
+    // With --analyze or --count-only, keep the maze as a bit per cell rather than a Grid
+    low_memory: bool,
+    directions: bool,
+    png_path: Option<String>,
+    svg_path: Option<String>,
+    comment_prefix: Option<String>,
+    // What format the maze is in, if it's not to be worked out from the file name or contents
+    format: Option<Format>,
+    // Refuse mazes whose border has gaps in it that aren't openings
+    check_border: bool,
+    // The maze has no wall round it, so its entrance and exit are the ones given rather than openings on its border
+    open_field: bool,
+    // Step off one edge of the maze and back on at the opposite one
+    toroidal: bool,
+    // The order to try each cell's neighbours in
+    neighbour_order: NeighbourOrder,
+    // Where to start and finish instead of the openings found in the maze file
+    entrance: Option<Coordinate>,
+    exit: Option<Coordinate>,
+}
+
+// Parse the command line into Options, or return a message explaining what was wrong with it
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut args = args.peekable();
+    let command = args.peek().and_then(|arg| Command::from_name(arg));
+    if command.is_some() {
+        args.next();
+    }
+    // generate's width and height, given as two numbers
+    let mut generate_numbers = Vec::new();
+    let mut maze_path = None;
+    let mut batch_dir = None;
+    let mut generate_size = None;
+    let mut seed = 0;
+    let mut algorithm = Algorithm::AStar;
+    let mut connectivity = Connectivity::Four;
+    let mut heuristic = None;
+    let mut heuristic_weight = None;
+    let (mut cost_x, mut cost_y) = (1, 1);
+    let mut output = OutputFormat::Text;
+    let mut bidirectional = false;
+    let mut hpa = false;
+    let mut contract = false;
+    let mut turn_penalty = None;
+    let mut best_effort = false;
+    let mut max_visited = None;
+    let mut any_angle = false;
+    let mut analyze = false;
+    let mut heatmap = false;
+    let mut optimal_cells = false;
+    let mut limits = SearchLimits::default();
+    let mut render = false;
+    let mut numbered = false;
+    let mut no_color = false;
+    let mut show_visited = false;
+    let mut animate = false;
+    let mut fps = 30;
+    let mut frames_dir = None;
+    let mut frame_every = None;
+    let mut quiet = false;
+    let mut progress = false;
+    let mut count_only = false;
+    let mut low_memory = false;
+    let mut directions = false;
+    let mut png_path = None;
+    let mut svg_path = None;
+    let mut comment_prefix = None;
+    let mut format = None;
+    let mut check_border = false;
+    let mut open_field = false;
+    let mut toroidal = false;
+    let mut neighbour_order = NeighbourOrder::default();
+    let mut entrance = None;
+    let mut exit = None;
+    while let Some(arg) = args.next() {
+        if let Some(command) = command.filter(|command| arg.starts_with("--") && !command.accepts(&arg)) {
+            return Err(format!("'{}' isn't an option of mazesolver {}", arg, command.name()));
+        }
+        match arg.as_str() {
+            "--algo" => {
+                let name = args.next().ok_or("--algo needs an algorithm name")?;
+                algorithm = name.parse()?;
+            },
+            "--connectivity" => {
+                let neighbours = args.next().ok_or("--connectivity needs 4 or 8")?;
+                connectivity = neighbours.parse()?;
+            },
+            "--heuristic" => {
+                let name = args.next().ok_or("--heuristic needs a heuristic name")?;
+                heuristic = Some(name.parse()?);
+            },
+            "--weight" => {
+                let value = args.next().ok_or("--weight needs a number, e.g. 1.5")?;
+                heuristic_weight = Some(value.parse::<f64>().ok().filter(|&weight| weight >= 1.0).ok_or(format!("bad weight '{}' (it must be at least 1)", value))?);
+            },
+            "--cost-x" => {
+                let value = args.next().ok_or("--cost-x needs a number")?;
+                cost_x = value.parse().ok().filter(|&cost| cost > 0).ok_or(format!("bad horizontal step cost '{}'", value))?;
+            },
+            "--cost-y" => {
+                let value = args.next().ok_or("--cost-y needs a number")?;
+                cost_y = value.parse().ok().filter(|&cost| cost > 0).ok_or(format!("bad vertical step cost '{}'", value))?;
+            },
+            "--bidirectional" => bidirectional = true,
+            "--hpa" => hpa = true,
+            "--contract" => contract = true,
+            "--turn-penalty" => {
+                let value = args.next().ok_or("--turn-penalty needs a number")?;
+                turn_penalty = Some(value.parse().map_err(|_| format!("bad turn penalty '{}'", value))?);
+            },
+            "--any-angle" => any_angle = true,
+            "--best-effort" => best_effort = true,
+            "--analyze" => analyze = true,
+            "--heatmap" => heatmap = true,
+            "--optimal-cells" => optimal_cells = true,
+            "--max-visited" => {
+                let value = args.next().ok_or("--max-visited needs a number")?;
+                max_visited = Some(value.parse().map_err(|_| format!("bad visited cell cap '{}'", value))?);
+            },
+            "--max-nodes" => {
+                let value = args.next().ok_or("--max-nodes needs a number")?;
+                limits.max_nodes = Some(value.parse().map_err(|_| format!("bad node limit '{}'", value))?);
+            },
+            "--timeout" => {
+                let value = args.next().ok_or("--timeout needs a number of milliseconds")?;
+                limits.timeout = Some(Duration::from_millis(value.parse().map_err(|_| format!("bad timeout '{}'", value))?));
+            },
+            "--output" => {
+                output = match args.next().ok_or("--output needs a format")?.as_str() {
+                    "text" => OutputFormat::Text,
+                    "json" => OutputFormat::Json,
+                    format => return Err(format!("unknown output format '{}' (expected text or json)", format)),
+                };
+            },
+            "--generate" => {
+                let size = args.next().ok_or("--generate needs a size, e.g. 21x11")?;
+                let (width, height) = size.split_once('x').ok_or("--generate size should look like 21x11")?;
+                let width: usize = width.parse().map_err(|_| format!("bad maze width '{}'", width))?;
+                let height: usize = height.parse().map_err(|_| format!("bad maze height '{}'", height))?;
+                if width < 3 || height < 3 {
+                    return Err("generated mazes must be at least 3x3".to_string());
                 }
+                generate_size = Some((width, height));
+            },
+            "--batch" => batch_dir = Some(args.next().ok_or("--batch needs a directory of mazes")?),
+            "--seed" => {
+                let value = args.next().ok_or("--seed needs a number")?;
+                seed = value.parse().map_err(|_| format!("bad seed '{}'", value))?;
+            },
+            "--render" => render = true,
+            "--numbered" => numbered = true,
+            "--no-color" => no_color = true,
+            "--visited" => show_visited = true,
+            "--animate" => animate = true,
+            "--fps" => {
+                let value = args.next().ok_or("--fps needs a number of frames per second")?;
+                fps = value.parse().ok().filter(|&fps| fps > 0).ok_or(format!("bad frame rate '{}'", value))?;
+            },
+            "--frames-dir" => frames_dir = Some(PathBuf::from(args.next().ok_or("--frames-dir needs a directory")?)),
+            "--frame-every" => {
+                let value = args.next().ok_or("--frame-every needs a number")?;
+                frame_every = Some(value.parse().ok().filter(|&every: &usize| every > 0).ok_or(format!("bad frame interval '{}'", value))?);
+            },
+            "--quiet" => quiet = true,
+            "--progress" => progress = true,
+            "--count-only" => count_only = true,
+            "--low-memory" => low_memory = true,
+            "--directions" => directions = true,
+            "--comment-prefix" => comment_prefix = Some(args.next().ok_or("--comment-prefix needs a prefix, e.g. \"# \"")?),
+            "--format" => format = Some(args.next().ok_or("--format needs a maze format")?.parse()?),
+            "--check-border" => check_border = true,
+            "--open-field" => open_field = true,
+            "--toroidal" => toroidal = true,
+            "--neighbour-order" => neighbour_order = args.next().ok_or("--neighbour-order needs an order, e.g. NESW")?.parse()?,
+            "--entrance" => entrance = Some(args.next().ok_or("--entrance needs a coordinate, e.g. 1,0")?.parse()?),
+            "--exit" => exit = Some(args.next().ok_or("--exit needs a coordinate, e.g. 19,10")?.parse()?),
+            "--png" => png_path = Some(args.next().ok_or("--png needs an output file")?),
+            "--svg" => svg_path = Some(args.next().ok_or("--svg needs an output file")?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option '{}'", arg)),
+            _ if command == Some(Command::Generate) => generate_numbers.push(arg),
+            // The maze to solve is given as the first positional argument; "-" means read the maze from stdin instead
+            _ if maze_path.is_none() => maze_path = Some(arg),
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    match command {
+        Some(Command::Render) => render = !heatmap && !optimal_cells,
+        Some(Command::Analyze) => analyze = true,
+        Some(Command::Generate) => {
+            let [width, height] = &generate_numbers[..] else {
+                return Err("generate needs a width and a height, e.g. generate 21 11".to_string());
+            };
+            let width: usize = width.parse().map_err(|_| format!("bad maze width '{}'", width))?;
+            let height: usize = height.parse().map_err(|_| format!("bad maze height '{}'", height))?;
+            if width < 3 || height < 3 {
+                return Err("generated mazes must be at least 3x3".to_string());
             }
-        };
-        exit_coordinates.reverse();
-        //print!("exit coordinates: {:?} \n", exit_coordinates);
-        // Get the entrance and exit coordinates
-        // We only pop the first two to simulate a "perfect maze"; a further expansion would be to allow for imperfect mazes
-        let entrance_location = exit_coordinates.pop().unwrap();
-        let exit_location = exit_coordinates.pop().unwrap();
-        println!("Grid constructed. ");
-        Ok(Grid {
-            width,
-            height,
-            entrance_location,
-            exit_location,
-            cells,
+            generate_size = Some((width, height));
+        },
+        Some(Command::Solve) | None => (),
+    }
+    if bidirectional && limits != SearchLimits::default() {
+        return Err("--max-nodes and --timeout can't be used with --bidirectional".to_string());
+    }
+    // A weight turns A* into weighted A*; the other algorithms don't have anything to weight
+    if let Some(weight) = heuristic_weight {
+        if algorithm != Algorithm::AStar {
+            return Err("--weight only works with --algo astar".to_string());
+        }
+        algorithm = Algorithm::WeightedAStar(weight);
+    }
+    // The animation is drawn on stdout, so it would end up in the middle of the JSON
+    if animate && output == OutputFormat::Json {
+        return Err("--animate can't be used with --output json".to_string());
+    }
+    // Hierarchical A* runs its own searches over clusters of the maze, so none of the options for the usual search apply to it
+    if hpa && (bidirectional || any_angle || count_only || connectivity == Connectivity::Eight || limits != SearchLimits::default()) {
+        return Err("--hpa can't be used with --bidirectional, --any-angle, --count-only, --connectivity 8, --max-nodes or --timeout".to_string());
+    }
+    // Likewise the corridor graph is searched with its own A*, moving four ways
+    if contract && (hpa || bidirectional || any_angle || count_only || connectivity == Connectivity::Eight || limits != SearchLimits::default()) {
+        return Err("--contract can't be used with --hpa, --bidirectional, --any-angle, --count-only, --connectivity 8, --max-nodes or --timeout".to_string());
+    }
+    if count_only && (bidirectional || any_angle) {
+        return Err("--count-only can't be used with --bidirectional or --any-angle".to_string());
+    }
+    if count_only && (render || show_visited || animate || directions || png_path.is_some() || svg_path.is_some() || output == OutputFormat::Json) {
+        return Err("--count-only never builds the path, so there's nothing to draw or print it with".to_string());
+    }
+    // Turns are counted by an A* of their own
+    if turn_penalty.is_some() && (algorithm != Algorithm::AStar || bidirectional || hpa || contract || any_angle || count_only) {
+        return Err("--turn-penalty searches with plain A*, so it can't be used with --algo, --weight, --bidirectional, --hpa, --contract, --any-angle or --count-only".to_string());
+    }
+    // The closest cell is picked from every cell the usual search reached, which the others don't keep track of
+    if best_effort && (bidirectional || hpa || contract || turn_penalty.is_some() || any_angle || count_only || low_memory || output == OutputFormat::Json) {
+        return Err("--best-effort can't be used with --bidirectional, --hpa, --contract, --turn-penalty, --any-angle, --count-only, --low-memory or --output json".to_string());
+    }
+    if max_visited.is_some() && (best_effort || bidirectional || hpa || contract || turn_penalty.is_some() || any_angle || count_only || low_memory
+                                 || limits != SearchLimits::default() || output == OutputFormat::Json) {
+        return Err("--max-visited can't be used with --best-effort, --bidirectional, --hpa, --contract, --turn-penalty, --any-angle, --count-only, --low-memory, --max-nodes, --timeout or --output json".to_string());
+    }
+    // The heatmap is all that gets printed, so there's no room for anything else
+    if heatmap && (analyze || any_angle || count_only || low_memory || render || directions || animate || frames_dir.is_some()
+                   || batch_dir.is_some() || output == OutputFormat::Json) {
+        return Err("--heatmap can't be used with --analyze, --any-angle, --count-only, --low-memory, --render, --directions, --animate, --frames-dir, --batch or --output json".to_string());
+    }
+    if optimal_cells && (heatmap || analyze || any_angle || count_only || low_memory || render || directions || animate || frames_dir.is_some()
+                         || batch_dir.is_some() || output == OutputFormat::Json) {
+        return Err("--optimal-cells can't be used with --heatmap, --analyze, --any-angle, --count-only, --low-memory, --render, --directions, --animate, --frames-dir, --batch or --output json".to_string());
+    }
+    if frame_every.is_some() && frames_dir.is_none() {
+        return Err("--frame-every only works with --frames-dir".to_string());
+    }
+    // Frames are drawn from the steps the usual search reports, which the searches with loops of their own don't
+    if frames_dir.is_some() && (hpa || contract || turn_penalty.is_some() || any_angle || count_only || low_memory || batch_dir.is_some()) {
+        return Err("--frames-dir can't be used with --hpa, --contract, --turn-penalty, --any-angle, --count-only, --low-memory or --batch".to_string());
+    }
+    // A BitGrid only knows which cells are walls, and only counts plain four-way steps
+    if low_memory && !(analyze || count_only) {
+        return Err("--low-memory only works with --analyze or --count-only".to_string());
+    }
+    if low_memory && (batch_dir.is_some() || hpa || contract || toroidal || check_border || entrance.is_some() || exit.is_some()
+                      || (cost_x, cost_y) != (1, 1) || connectivity == Connectivity::Eight || limits != SearchLimits::default()
+                      || format.is_some_and(|format| format != Format::Ascii)) {
+        return Err("--low-memory only reads plain ASCII mazes, and counts four-way steps that all cost 1; it can't be used with --batch, --hpa, --contract, --toroidal, --check-border, --entrance, --exit, --cost-x, --cost-y, --connectivity 8, --max-nodes, --timeout or --format".to_string());
+    }
+    // Compass directions only name single straight steps, so there's nothing to call a diagonal or a line of sight
+    if directions && (any_angle || connectivity == Connectivity::Eight) {
+        return Err("--directions can't be used with --any-angle or --connectivity 8".to_string());
+    }
+    // Theta*'s lines of sight are drawn straight across the maze, so they can't follow a path round the edge and back
+    if toroidal && any_angle {
+        return Err("--toroidal can't be used with --any-angle".to_string());
+    }
+    // An open field has no openings of its own to find, and no border wall to check
+    if open_field && (entrance.is_none() || exit.is_none() || check_border) {
+        return Err("--open-field needs both --entrance and --exit, and can't be used with --check-border".to_string());
+    }
+    // Every maze in a batch has its openings in different places
+    if batch_dir.is_some() && (entrance.is_some() || exit.is_some()) {
+        return Err("--entrance and --exit can't be used with --batch".to_string());
+    }
+    // A batch prints one row of its table for each maze, with no room for a drawing, a path or a path that stops short of an exit
+    if batch_dir.is_some() && (render || numbered || show_visited || animate || progress || directions || png_path.is_some() || svg_path.is_some()
+                               || best_effort || max_visited.is_some() || any_angle || count_only || analyze || output == OutputFormat::Json) {
+        return Err("--batch only prints a table of results, so it can't be used with --render, --numbered, --visited, --animate, --progress, --directions, --png, --svg, --best-effort, --max-visited, --any-angle, --count-only, --analyze or --output json".to_string());
+    }
+    let mode = match (generate_size, batch_dir) {
+        (Some((width, height)), _) => Mode::Generate { width, height, seed },
+        (None, Some(_)) if maze_path.is_some() => return Err("--batch solves a whole directory, so it can't be given a maze file too".to_string()),
+        (None, Some(batch_dir)) => Mode::Batch(batch_dir),
+        (None, None) => Mode::Solve(maze_path.ok_or("no maze file given")?),
+    };
+    Ok(Options {
+        mode,
+        algorithm,
+        connectivity,
+        // Without a --heuristic, pick the one that suits the connectivity
+        heuristic: heuristic.unwrap_or(Heuristic::default_for(connectivity)),
+        step_costs: (cost_x, cost_y),
+        bidirectional,
+        hpa,
+        turn_penalty,
+        best_effort,
+        max_visited,
+        contract,
+        analyze,
+        heatmap,
+        optimal_cells,
+        any_angle,
+        limits,
+        output,
+        render,
+        numbered,
+        no_color,
+        show_visited,
+        animate_fps: animate.then_some(fps),
+        frames_dir,
+        frame_every: frame_every.unwrap_or(1),
+        quiet,
+        progress,
+        count_only,
+        low_memory,
+        directions,
+        png_path,
+        svg_path,
+        comment_prefix,
+        format,
+        check_border,
+        open_field,
+        toroidal,
+        neighbour_order,
+        entrance,
+        exit,
+    })
+}
+
+// Run whichever search the options ask for on one maze
+fn run_search(maze: &mut Grid<Cell>, options: &Options, on_step: Option<&mut dyn FnMut(&SearchStep)>)
+              -> Result<(Option<Solution>, SearchStats), SearchError> {
+    if options.bidirectional {
+        Ok(solve_bidirectional(maze, options.connectivity, options.heuristic, on_step))
+    }
+    else if options.hpa {
+        Ok(solve_hpa(maze, HPA_CLUSTER_SIZE))
+    }
+    else if options.contract {
+        Ok(solve_contracted(maze))
+    }
+    else if options.best_effort {
+        solve_best_effort(maze, options.algorithm, options.connectivity, options.heuristic, options.limits).map(|(solution, stats)| (Some(solution), stats))
+    }
+    else if let Some(max_visited) = options.max_visited {
+        // A partial path is handed back as a solution that ends short of an exit, the way a best-effort one is
+        Ok(match solve_with_max_visited(maze, options.algorithm, options.connectivity, options.heuristic, max_visited)? {
+            SolveOutcome::Found { path, exit, cost, stats } => (Some(Solution { path, exit, cost }), stats),
+            SolveOutcome::Unreachable { stats } => (None, stats),
+            SolveOutcome::Partial { path, cost, stats, .. } => (Some(Solution { exit: *path.last().unwrap(), path, cost }), stats),
         })
     }
+    else if let Some(penalty) = options.turn_penalty {
+        solve_with_turn_penalty(maze, options.connectivity, options.heuristic, penalty, options.limits)
+    }
+    else {
+        solve_with_limits(maze, options.algorithm, options.connectivity, options.heuristic, options.limits, on_step)
+    }
 }
 
-fn main() {
-    let mut maze = Grid::new(Path::new("mazes/maze-VLarge.txt")).unwrap();
-    //println!("maze: {:?} ", maze);
+// Save one frame of the search as frame-000000.txt and so on, numbered so the files sort in the order they were saved
+fn save_frame(frames_dir: &Path, number: usize, frame: &str) -> io::Result<()> {
+    fs::write(frames_dir.join(format!("frame-{:06}.txt", number)), frame)
+}
 
-    // Declare all our collections to store our working data
-    let mut open_set = BinaryHeap::new();
-    let mut closed_set = HashSet::new();
-    let mut current_cell = maze.cells[maze.entrance_location.y * maze.width + maze.entrance_location.x];
-
-    //println!("current_cell: {:?} ", current_cell);
-    open_set.push(current_cell);
-    while !open_set.is_empty() {
-        // Get the lowest cost item from the open set
-        // The open set will always pop the lowest cost item due to our custom definition of Ord on the Cells in open_set
-        current_cell = open_set.pop().unwrap();
-        if current_cell.coordinate == maze.exit_location {
-            // If the popped cell is the exit, we're done, so break the loop
-            break;
-        }
-        // If the popped cell is not the exit, add it to the closed set and get its neighbours
-        closed_set.insert(current_cell.coordinate);
-        let mut neighbours = Vec::new();
-        if current_cell.coordinate.x > 0 {
-            neighbours.push(Coordinate{x: current_cell.coordinate.x - 1, y: current_cell.coordinate.y});
-        }
-        if current_cell.coordinate.x < maze.width - 1 {
-            neighbours.push(Coordinate{x: current_cell.coordinate.x + 1, y: current_cell.coordinate.y});
-        }
-        if current_cell.coordinate.y > 0 {
-            neighbours.push(Coordinate{x: current_cell.coordinate.x, y: current_cell.coordinate.y - 1});
-        }
-        if current_cell.coordinate.y < maze.height - 1 {
-            neighbours.push(Coordinate{x: current_cell.coordinate.x, y: current_cell.coordinate.y + 1});
-        }
-
-        // Loop across the neighbours we just found
-        for neighbour in neighbours {
-            // If a neighbour is in the closed set, skip it
-            if closed_set.contains(&neighbour) {
-                //print!("skipping neighbour found in closed set \n");
+// Solve every .txt maze in a directory, in name order, printing a line for each and a total at the end
+// A maze that won't load or solve (or, with --check-border, has gaps in its border) gets its error printed in its row, and the
+// rest of the batch carries on. The table goes to out, which is stdout outside the tests
+fn solve_batch(batch_dir: &Path, options: &Options, out: &mut impl Write) -> Result<(), MazeError> {
+    let config = GridConfig { comment_prefix: options.comment_prefix.clone(), ..GridConfig::default() };
+    let mut maze_paths: Vec<PathBuf> = fs::read_dir(batch_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        // Any file named as a maze in one of the formats, gzipped ones included (they're read just like plain ones with the gzip feature)
+        .filter(|path| Format::from_extension(path).is_some())
+        .collect();
+    maze_paths.sort();
+    let (mut solved, mut failed) = (0, 0);
+    let mut total_nodes = 0;
+    let mut total_time = Duration::ZERO;
+    writeln!(out, "{:<30} {:>11} {:>12} {:>15} {:>14}", "Maze", "Size", "Path length", "Nodes expanded", "Time taken")?;
+    for maze_path in maze_paths {
+        let name = maze_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let loaded = Grid::load(&maze_path, options.format, &config)
+            .and_then(|maze| if options.check_border { maze.check_border().map(|_| maze) } else { Ok(maze) });
+        let mut maze = match loaded {
+            Ok(maze) => maze,
+            Err(error) => {
+                writeln!(out, "{:<30} error: {}", name, error)?;
+                failed += 1;
                 continue;
             }
-            //print!("neighbour.x: {}, neighbour.y: {}, width: {}, height: {} \n", neighbour.x, neighbour.y, maze.width, maze.height);
-
-            // Get the neighbour cell itself from the maze using its coordinates
-            let neighbour_cell = &mut maze.cells[neighbour.y * maze.width + neighbour.x];
-            if neighbour_cell.cell_type == CellType::Wall || neighbour_cell.cell_type == CellType::Entrance {
-                // If the neighbour is a wall or an entrance, we can safely skip it
-                // (walls are irrelevant, the entrance is already in the closed set even on the first iteration)
-                //print!("skipping wall or entrance: {:?} at {:?} \n", neighbour_cell.cell_type, neighbour_cell.coordinate);
+        };
+        maze.set_step_costs(options.step_costs.0, options.step_costs.1);
+        maze.set_toroidal(options.toroidal);
+        maze.set_neighbour_order(options.neighbour_order);
+        let size = format!("{}x{}", maze.width(), maze.height());
+        let (solution, stats) = match run_search(&mut maze, options, None) {
+            Ok(result) => result,
+            Err(error) => {
+                writeln!(out, "{:<30} {:>11} error: {}", name, size, error)?;
+                failed += 1;
                 continue;
             }
+        };
+        let path_length = if solution.is_some() { stats.path_length.to_string() } else { "no path".to_string() };
+        writeln!(out, "{:<30} {:>11} {:>12} {:>15} {:>14}", name, size, path_length, stats.nodes_expanded, format!("{:?}", stats.elapsed))?;
+        solved += 1;
+        total_nodes += stats.nodes_expanded;
+        total_time += stats.elapsed;
+    }
+    writeln!(out, "Total: {} solved, {} failed, {} nodes expanded, {:?}", solved, failed, total_nodes, total_time)?;
+    Ok(())
+}
 
-            // A neighbour cell's cost is the cost of the current cell plus 1
-            let tentative_cost = current_cell.cost + 1;
-            // If the neighbour cell is not in the open set, or if the tentative cost is less than the neighbour cell's cost, update the neighbour cell
-            // We update on the basis of the tentative cost being less than the neighbour cell's cost because we want to find the shortest path, 
-            // and a neighbour may have already been found in another exploration of the maze, but with a higher cost
-            // We only ever care about the lower cost; if we found a path to a cell with a lower cost, great!
-            if !open_set.iter().any(|heap_item| heap_item == neighbour_cell) || tentative_cost < neighbour_cell.cost {
-                neighbour_cell.parent_coord = current_cell.coordinate;
-                neighbour_cell.cost = tentative_cost;
-                neighbour_cell.manhattan_from_exit = (neighbour_cell.coordinate.x as isize - maze.exit_location.x as isize).unsigned_abs() + (neighbour_cell.coordinate.y as isize - maze.exit_location.y as isize).unsigned_abs();
-                // Now that we've updated the neighbour, if it's not in the open set, add it
-                // On top of that, if it's in the closed set, remove it from the closed set so we don't skip over it later when we shouldn't
-                if !open_set.iter().any(|heap_item| heap_item == neighbour_cell) {
-                    open_set.push(*neighbour_cell);
-                    closed_set.remove(&neighbour_cell.coordinate);
-                }
+fn print_analysis(analysis: &MazeAnalysis, output: OutputFormat) {
+    if output == OutputFormat::Json {
+        println!("{}", analysis_to_json(analysis));
+        return;
+    }
+    let exits: Vec<String> = analysis.exits.iter().map(|exit| exit.to_string()).collect();
+    println!("Size: {}x{} ", analysis.width, analysis.height);
+    println!("Walls: {} ", analysis.walls);
+    println!("Open cells: {} ", analysis.open_cells);
+    println!("Openings: {} ", analysis.openings);
+    println!("Perfect maze: {} ", if analysis.perfect { "yes" } else { "no" });
+    println!("Dead ends: {} ", analysis.dead_ends);
+    println!("Junctions: {} ", analysis.junctions);
+    println!("Entrance: {} ", analysis.entrance);
+    println!("Exits: {} ", exits.join(" "));
+}
+
+// What --count-only prints: the path's length and cost, but not the path
+fn print_cost(cost: Option<usize>, stats: &SearchStats) {
+    match cost {
+        Some(cost) => {
+            info!("Solution found");
+            println!("Path length (cells): {} ", stats.path_length);
+            println!("Path cost: {} ", cost);
+        },
+        None => println!("No path found. "),
+    }
+    println!("Nodes expanded: {} ", stats.nodes_expanded);
+    println!("Peak frontier size: {} ", stats.max_frontier_size);
+    println!("Time taken: {:?} ", stats.elapsed);
+}
+
+// Explain how to run a command, with every option it takes, or how to run mazesolver at all if there's no command
+fn print_usage(command: Option<Command>) {
+    match command {
+        Some(command) => eprintln!("Usage: {}", command.usage()),
+        None => {
+            eprintln!("Usage: mazesolver [options] <maze file>");
+            eprintln!("       mazesolver [options] -    (read the maze from stdin)");
+            eprintln!("       mazesolver --generate <width>x<height> [--seed <n>]");
+            eprintln!("       mazesolver [options] --batch <directory>    (solve every maze file in it)");
+            for command in [Command::Solve, Command::Render, Command::Analyze, Command::Generate] {
+                eprintln!("       {}", command.usage());
             }
+        },
+    }
+    eprintln!("Options:");
+    for (flags, usage, description) in OPTIONS {
+        if command.is_none_or(|command| flags.iter().any(|flag| command.accepts(flag))) {
+            eprintln!("  {:<58}{}", usage, description);
+        }
+    }
+}
+
+// Writes log messages to stderr, so they never end up mixed in with the solution on stdout
+struct StderrLogger;
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+// Log at the level RUST_LOG asks for (error, warn, info, debug, trace or off), or info without it
+// --quiet turns anything below a warning off, whatever RUST_LOG says
+fn init_logging(quiet: bool) {
+    let mut level = env::var("RUST_LOG").ok().and_then(|level| level.parse().ok()).unwrap_or(LevelFilter::Info);
+    if quiet {
+        level = level.min(LevelFilter::Warn);
+    }
+    if log::set_logger(&StderrLogger).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+fn main() -> Result<(), MazeError> {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            print_usage(env::args().nth(1).and_then(|arg| Command::from_name(&arg)));
+            process::exit(2);
+        }
+    };
+    init_logging(options.quiet);
+    let maze_path = match options.mode {
+        Mode::Solve(ref maze_path) => maze_path.clone(),
+        Mode::Generate { width, height, seed } => {
+            print!("{}", generate_text(width, height, seed));
+            return Ok(());
+        }
+        Mode::Batch(ref batch_dir) => {
+            solve_batch(Path::new(batch_dir), &options, &mut io::stdout())?;
+            return Ok(());
+        }
+    };
+    let openings = options.entrance.zip(options.exit).filter(|_| options.open_field);
+    let config = GridConfig { comment_prefix: options.comment_prefix.clone(), openings, ..GridConfig::default() };
+    // Read the maze into a BitGrid a line at a time, for mazes too big to hold as a Grid
+    if options.low_memory {
+        let maze = if maze_path == "-" {
+            BitGrid::from_reader_with_config(io::stdin().lock(), &config)?
+        }
+        else {
+            BitGrid::from_reader_with_config(BufReader::new(File::open(&maze_path)?), &config)?
+        };
+        if options.analyze {
+            print_analysis(&maze.analyze(), options.output);
+        }
+        else {
+            let (steps, stats) = maze.shortest_path_steps();
+            print_cost(steps, &stats);
+        }
+        return Ok(());
+    }
+    let mut maze = if maze_path == "-" {
+        Grid::from_reader_in(io::stdin().lock(), options.format, &config)?
+    }
+    else {
+        Grid::load(Path::new(&maze_path), options.format, &config)?
+    };
+    //println!("maze: {:?} ", maze);
+    maze.set_step_costs(options.step_costs.0, options.step_costs.1);
+    maze.set_toroidal(options.toroidal);
+    maze.set_neighbour_order(options.neighbour_order);
+    if options.entrance.is_some() || options.exit.is_some() {
+        maze.set_openings(options.entrance, options.exit)?;
+    }
+    if options.check_border {
+        maze.check_border()?;
+    }
+
+    if options.analyze {
+        print_analysis(&analyze(&maze), options.output);
+        return Ok(());
+    }
+    if options.heatmap {
+        print!("{}", render_heatmap(&maze, &distance_field(&maze, maze.entrance(), options.connectivity)));
+        return Ok(());
+    }
+    if options.optimal_cells {
+        let cells: Vec<Coordinate> = optimal_cells(&maze, options.connectivity).into_iter().collect();
+        if cells.is_empty() {
+            println!("No path found. ");
+            return Ok(());
+        }
+        print!("{}", render(&maze, &cells, "*"));
+        println!("Cells on a shortest path: {} ", cells.len());
+        return Ok(());
+    }
+
+    // Any-angle paths skip from turning point to turning point, so none of the step-by-step output below applies to them
+    if options.any_angle {
+        let (solution, stats) = solve_theta_star(&maze);
+        let Some(solution) = solution else {
+            println!("No path found. ");
+            return Ok(());
+        };
+        let turning_points: Vec<String> = solution.path.iter().map(|point| point.to_string()).collect();
+        info!("Solution found");
+        println!("Path length: {:.2} ", euclidean_length(&solution.path));
+        println!("Nodes expanded: {} ", stats.nodes_expanded);
+        println!("Time taken: {:?} ", stats.elapsed);
+        println!("Turning points: {} ", turning_points.join(" "));
+        return Ok(());
+    }
+
+    // Only the numbers are wanted, so the path is never built; on a huge maze that saves holding every cell of it at once
+    if options.count_only {
+        let (cost, stats) = match solve_cost_with_limits(&mut maze, options.algorithm, options.connectivity, options.heuristic, options.limits, None) {
+            Ok(result) => result,
+            Err(error) => {
+                eprintln!("Error: {}", error);
+                process::exit(1);
+            }
+        };
+        print_cost(cost, &stats);
+        return Ok(());
+    }
+
+    // Keep track of the cells the search visits, and draw a frame after every step if we're animating
+    // solve_with holds the maze mutably while it runs, so the frames are drawn from a copy of it
+    let canvas = maze.clone();
+    let mut visited = HashSet::new();
+    let mut expanded = 0;
+    let mut closest = usize::MAX;
+    // Frames are numbered in the order they're saved, and the first one that can't be written stops the rest
+    let mut frames_saved = 0;
+    let mut frame_error = None;
+    if let Some(frames_dir) = &options.frames_dir {
+        fs::create_dir_all(frames_dir)?;
+    }
+    let mut on_step = |step: &SearchStep| {
+        visited.insert(step.expanded);
+        expanded += 1;
+        if options.progress {
+            closest = closest.min(options.heuristic.estimate_nearest(&canvas, step.expanded, canvas.exits(), options.connectivity));
+            // Printing every step would take longer than the search, so only now and then
+            // The carriage return puts each line over the last one; it goes to stderr to keep it out of the results
+            if expanded % PROGRESS_INTERVAL == 0 {
+                eprint!("\rExpanded {} nodes, frontier {}, closest estimate to an exit {} ", expanded, step.frontier.len(), closest);
+            }
+        }
+        if let Some(fps) = options.animate_fps {
+            let frontier = step.frontier.iter().copied().collect();
+            // Move the cursor back to the top left and draw over the last frame, which flickers less than clearing the screen
+            print!("\x1b[H{}", render_search(&canvas, &[], &visited, &frontier, "*"));
+            thread::sleep(Duration::from_secs_f64(1.0 / fps as f64));
+        }
+        if let Some(frames_dir) = options.frames_dir.as_ref().filter(|_| frame_error.is_none() && expanded % options.frame_every == 0) {
+            let frontier = step.frontier.iter().copied().collect();
+            let frame = render_search(&canvas, &[], &visited, &frontier, "*");
+            frame_error = save_frame(frames_dir, frames_saved, &frame).err();
+            frames_saved += 1;
         }
+    };
+    // Only watch the search if we're going to draw what it did
+    let step_option: Option<&mut dyn FnMut(&SearchStep)> = if options.show_visited || options.animate_fps.is_some() || options.frames_dir.is_some() || options.progress {
+        Some(&mut on_step)
     }
-    println!("Solution found. ");
-    let mut path = Vec::new();
-    let mut current_cell = maze.cells[maze.exit_location.y * maze.width + maze.exit_location.x];
-    // Loop to backtrack through the complete path and reconstruct it.
-    while current_cell.coordinate != maze.entrance_location {
-        path.push(current_cell.coordinate);
-        current_cell = maze.cells[current_cell.parent_coord.y * maze.width + current_cell.parent_coord.x];
+    else {
+        None
+    };
+    if options.animate_fps.is_some() {
+        print!("\x1b[2J");
+    }
+    let result = run_search(&mut maze, &options, step_option);
+    // Finish the status line so what comes next starts on a line of its own
+    if options.progress {
+        eprintln!();
+    }
+    let (solution, stats) = match result {
+        Ok(result) => result,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            process::exit(1);
+        }
+    };
+    // The last frame of the animation shows the path found, if there was one
+    if options.animate_fps.is_some() {
+        let path = solution.as_ref().map_or(&[][..], |solution| &solution.path);
+        print!("\x1b[H{}", render_visited(&maze, path, &visited, "*"));
+    }
+    // Likewise the last frame saved, so the frames end on the path
+    if let Some(frames_dir) = &options.frames_dir {
+        if let Some(error) = frame_error {
+            return Err(error.into());
+        }
+        let path = solution.as_ref().map_or(&[][..], |solution| &solution.path);
+        save_frame(frames_dir, frames_saved, &render_visited(&maze, path, &visited, "*"))?;
+    }
+    if options.output == OutputFormat::Json {
+        println!("{}", solution_to_json(&maze, solution.as_ref()));
+        return Ok(());
+    }
+    let (path, exit, cost, stats) = match SolveOutcome::from((solution, stats)) {
+        SolveOutcome::Found { path, exit, cost, stats } => (path, exit, cost, stats),
+        SolveOutcome::Unreachable { stats } => {
+            println!("No path found. ");
+            println!("Nodes expanded: {} ", stats.nodes_expanded);
+            println!("Peak frontier size: {} ", stats.max_frontier_size);
+            println!("Time taken: {:?} ", stats.elapsed);
+            return Ok(());
+        },
+        SolveOutcome::Partial { .. } => unreachable!("a partial outcome only comes from solve_with_max_visited"),
+    };
+    if maze.exits().contains(&exit) {
+        info!("Solution found");
+    }
+    else if options.max_visited.is_some() {
+        println!("Search stopped at the visited cell cap; the path below goes to the closest cell it reached. ");
+    }
+    else {
+        println!("No path found; the path below gets as close to an exit as it can. ");
+    }
+    println!("Path length (cells): {} ", stats.path_length);
+    println!("Path cost: {} ", cost);
+    println!("Nodes expanded: {} ", stats.nodes_expanded);
+    println!("Peak frontier size: {} ", stats.max_frontier_size);
+    println!("Time taken: {:?} ", stats.elapsed);
+    println!("{}: {} ", if maze.exits().contains(&exit) { "Exit reached" } else { "Closest cell reached" }, exit);
+    if options.directions {
+        println!("Directions: {} ", path_to_directions(&maze, &path).join(" "));
+    }
+    if options.render {
+        // Colour the maze only when it's going straight to a terminal, and nobody has asked us not to
+        // (see no-color.org: NO_COLOR turns colour off when it's set to anything but an empty string)
+        let no_color_env = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        if options.numbered {
+            print!("{}", render_numbered(&maze, &path));
+        }
+        else if !options.no_color && !no_color_env && io::stdout().is_terminal() {
+            print!("{}", render_colored(&maze, &path, &visited));
+        }
+        else {
+            print!("{}", render_visited(&maze, &path, &visited, "*"));
+        }
+    }
+    if let Some(png_path) = &options.png_path {
+        #[cfg(feature = "image")]
+        mazesolver::save_png(&maze, &path, 4, Path::new(png_path))?;
+        #[cfg(not(feature = "image"))]
+        eprintln!("Can't write {}: mazesolver was built without the image feature", png_path);
+    }
+    if let Some(svg_path) = &options.svg_path {
+        fs::write(svg_path, to_svg(&maze, &path))?;
     }
-    path.push(maze.entrance_location);
-    path.reverse();
-    println!("Path length: {} ", path.len());
     //print!("path: {:?} \n", path);
     //print!("maze: {:?} \n", maze);
-}
\ No newline at end of file
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(args: &[&str]) -> Options {
+        parse_args(args.iter().map(|arg| arg.to_string())).unwrap()
+    }
+
+    // One maze that solves, one that won't load, one with no way through and a file that isn't a maze at all
+    #[test]
+    fn a_batch_reports_each_maze_and_carries_on_past_errors() {
+        let batch_dir = env::temp_dir().join(format!("mazesolver-batch-{}", process::id()));
+        fs::create_dir_all(&batch_dir).unwrap();
+        fs::copy(concat!(env!("CARGO_MANIFEST_DIR"), "/mazes/maze-Easy.txt"), batch_dir.join("a-easy.txt")).unwrap();
+        fs::write(batch_dir.join("b-ragged.txt"), "#-#\n#-\n#-#").unwrap();
+        fs::write(batch_dir.join("c-sealed.txt"), "#####\n--#--\n#####").unwrap();
+        fs::write(batch_dir.join("notes.md"), "not a maze").unwrap();
+        let mut out = Vec::new();
+        let result = solve_batch(&batch_dir, &options(&["--batch", batch_dir.to_str().unwrap()]), &mut out);
+        fs::remove_dir_all(&batch_dir).unwrap();
+        result.unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 5, "{}", out);
+        assert!(lines[1].starts_with("a-easy.txt") && lines[1].contains("20x10") && lines[1].contains(" 27 "), "{}", lines[1]);
+        assert!(lines[2].starts_with("b-ragged.txt") && lines[2].contains("error:"), "{}", lines[2]);
+        assert!(lines[3].starts_with("c-sealed.txt") && lines[3].contains("no path"), "{}", lines[3]);
+        assert!(lines[4].starts_with("Total: 2 solved, 1 failed"), "{}", lines[4]);
+    }
+
+    #[test]
+    fn a_batch_checks_each_border_when_asked() {
+        let batch_dir = env::temp_dir().join(format!("mazesolver-batch-border-{}", process::id()));
+        fs::create_dir_all(&batch_dir).unwrap();
+        fs::copy(concat!(env!("CARGO_MANIFEST_DIR"), "/mazes/maze-Easy.txt"), batch_dir.join("a-easy.txt")).unwrap();
+        fs::write(batch_dir.join("b-stray-gap.txt"), "#####\n--#--\n----#\n#-###\n#####").unwrap();
+        let mut out = Vec::new();
+        let result = solve_batch(&batch_dir, &options(&["--batch", batch_dir.to_str().unwrap(), "--check-border"]), &mut out);
+        fs::remove_dir_all(&batch_dir).unwrap();
+        result.unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[1].starts_with("a-easy.txt") && lines[1].contains(" 27 "), "{}", lines[1]);
+        assert!(lines[2].starts_with("b-stray-gap.txt") && lines[2].contains("error: maze border has gaps"), "{}", lines[2]);
+        assert!(lines[3].starts_with("Total: 1 solved, 1 failed"), "{}", lines[3]);
+    }
+
+    #[test]
+    fn a_batch_rejects_options_it_has_no_room_to_print() {
+        for flags in [&["--render"][..], &["--output", "json"], &["--directions"], &["--png", "out.png"], &["--svg", "out.svg"], &["--visited"],
+                      &["--best-effort"], &["--max-visited", "10"]] {
+            let args = ["--batch", "mazes"].iter().chain(flags).map(|arg| arg.to_string());
+            assert!(parse_args(args).is_err_and(|message| message.starts_with("--batch only prints a table")), "{:?}", flags);
+        }
+        assert!(parse_args(["--batch", "mazes", "--algo", "bfs"].iter().map(|arg| arg.to_string())).is_ok());
+    }
+
+    #[test]
+    fn entrance_and_exit_are_read_as_x_then_y() {
+        let options = options(&["maze.txt", "--entrance", "19,8", "--exit", "1,0"]);
+        assert_eq!((options.entrance, options.exit), (Some(Coordinate { x: 19, y: 8 }), Some(Coordinate { x: 1, y: 0 })));
+        assert!(parse_args(["maze.txt", "--entrance", "19"].iter().map(|arg| arg.to_string())).is_err());
+    }
+}
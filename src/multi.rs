@@ -0,0 +1,79 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, solve::Connectivity};
+
+// The shortest path from the entrance to each of goals, entrance first, all from one run of Dijkstra rather than a search each
+// The search carries on past the first goal it reaches and stops once every goal has been, or once nothing more can be
+// reached; a goal that can't be reached (walled off, a wall itself, or outside the maze) is left out of the map
+// Unlike solve, the maze's own exits are just ordinary cells here, and the grid's search state is left untouched
+pub fn solve_multi(grid: &Grid<Cell>, goals: &[Coordinate], connectivity: Connectivity) -> HashMap<Coordinate, Vec<Coordinate>> {
+    let mut open_set = BinaryHeap::new();
+    let mut best_costs = HashMap::new();
+    let mut parents = HashMap::new();
+    let mut goals_left: HashSet<Coordinate> = goals.iter().copied().collect();
+    let mut goals_reached = Vec::new();
+
+    let entrance = grid.entrance_location;
+    best_costs.insert(entrance, 0);
+    open_set.push(Cell { cost: 0, f_score: 0, ..grid[entrance] });
+    while let Some(current_cell) = open_set.pop() {
+        if current_cell.cost > best_costs[&current_cell.coordinate] {
+            continue;
+        }
+        // Cells come out cheapest first, so the first time a goal comes out its route is as cheap as it gets
+        if goals_left.remove(&current_cell.coordinate) {
+            goals_reached.push(current_cell.coordinate);
+            if goals_left.is_empty() {
+                break;
+            }
+        }
+        for neighbour in grid.neighbours(current_cell.coordinate, connectivity) {
+            let mut neighbour_cell = grid[neighbour];
+            if neighbour_cell.cell_type == CellType::Wall {
+                continue;
+            }
+            let tentative_cost = current_cell.cost + connectivity.step_cost_between(grid, current_cell.coordinate, neighbour) * neighbour_cell.weight;
+            if best_costs.get(&neighbour).is_some_and(|&best_cost| tentative_cost >= best_cost) {
+                continue;
+            }
+            best_costs.insert(neighbour, tentative_cost);
+            parents.insert(neighbour, current_cell.coordinate);
+            neighbour_cell.cost = tentative_cost;
+            neighbour_cell.f_score = tentative_cost;
+            open_set.push(neighbour_cell);
+        }
+    }
+
+    goals_reached.into_iter().map(|goal| {
+        let mut path = vec![goal];
+        while let Some(&parent) = parents.get(path.last().unwrap()) {
+            path.push(parent);
+        }
+        path.reverse();
+        (goal, path)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_maze, solve::solve_between};
+
+    // Each path should cost what a search of its own to that goal finds; the wall in the corner can't be reached at all
+    #[test]
+    fn each_goal_gets_its_shortest_path() {
+        let grid = load_maze("maze-Easy");
+        let goals = [(18, 9), (3, 4), (10, 8)].map(|(x, y)| Coordinate { x, y });
+        let paths = solve_multi(&grid, &[goals[0], goals[1], goals[2], Coordinate { x: 0, y: 0 }], Connectivity::Four);
+        assert_eq!(paths.len(), 3);
+        for goal in goals {
+            let path = &paths[&goal];
+            assert_eq!((path.first(), path.last()), (Some(&grid.entrance()), Some(&goal)));
+            let (solution, _) = solve_between(&mut grid.clone(), grid.entrance(), goal).unwrap();
+            assert!(path.windows(2).all(|step| grid.neighbours(step[0], Connectivity::Four).contains(&step[1])), "{}", goal);
+            assert!(path.iter().all(|&coordinate| grid[coordinate].cell_type != CellType::Wall), "{}", goal);
+            // Every step costs 1 in maze-Easy
+            assert_eq!(path.len() - 1, solution.unwrap().cost, "{}", goal);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::{sync::atomic::{AtomicUsize, Ordering}, time::Instant};
+
+use rayon::prelude::*;
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, solve::{Connectivity, SearchStats, Solution}};
+
+// Marks a cell no layer has reached yet in the parents array
+const UNREACHED: usize = usize::MAX;
+
+// What it costs to follow path across the grid, counting the weight of every cell stepped into
+// BFS doesn't keep track of costs as it goes, so they're added up once the path is known
+fn path_cost(grid: &Grid<Cell>, path: &[Coordinate], connectivity: Connectivity) -> usize {
+    path.windows(2).map(|step| connectivity.step_cost_between(grid, step[0], step[1]) * grid[step[1]].weight).sum()
+}
+
+// Breadth-first search that expands each layer of the frontier across all of rayon's threads at once
+// Returns a path with the fewest steps from the entrance to an exit, the same length as solve_bfs finds;
+// like BFS it takes no notice of weighted terrain
+//
+// Why this is still a breadth-first search: every cell in a layer is the same number of steps from the entrance, and
+// the whole layer is expanded before the next one starts, just as BFS's queue would do it. Each cell's parent lives in
+// an atomic, and a neighbour is only added to the next layer by the thread whose compare_exchange moves its parent
+// from UNREACHED to the cell being expanded. That can only succeed once per cell, so every cell is claimed exactly
+// once, by a cell one step closer to the entrance, and so it joins the earliest layer it could be in. If several cells
+// in a layer race to claim the same neighbour, any of them winning gives a route of the same length, so only which of
+// the equally short paths comes back can change from run to run
+pub fn solve_parallel(grid: &Grid<Cell>, connectivity: Connectivity) -> (Option<Solution>, SearchStats) {
+    let start_time = Instant::now();
+    let mut stats = SearchStats::default();
+    let index = |coordinate: Coordinate| grid.index_of(coordinate);
+    let parents: Vec<AtomicUsize> = (0..grid.cells.len()).map(|_| AtomicUsize::new(UNREACHED)).collect();
+    // Borrowed once up here so each thread's closure can share it
+    let parents = &parents;
+    parents[index(grid.entrance_location)].store(index(grid.entrance_location), Ordering::Relaxed);
+
+    let mut frontier = vec![grid.entrance_location];
+    let mut exit_reached = None;
+    while !frontier.is_empty() {
+        // Every exit in this layer is equally close, so take whichever comes first in the maze to keep the answer stable
+        if let Some(&exit) = grid.exits.iter().find(|exit| frontier.contains(exit)) {
+            exit_reached = Some(exit);
+            break;
+        }
+        stats.nodes_expanded += frontier.len();
+        frontier = frontier.par_iter()
+            .flat_map_iter(|&coordinate| {
+                grid.neighbours(coordinate, connectivity).into_iter().filter(move |&neighbour| {
+                    // The entrance already has a parent, so it can never be claimed again
+                    grid[neighbour].cell_type != CellType::Wall
+                        && parents[index(neighbour)].compare_exchange(UNREACHED, index(coordinate), Ordering::Relaxed, Ordering::Relaxed).is_ok()
+                })
+            })
+            .collect();
+        stats.max_frontier_size = stats.max_frontier_size.max(frontier.len());
+    }
+
+    let Some(exit) = exit_reached else {
+        stats.elapsed = start_time.elapsed();
+        return (None, stats);
+    };
+    // Every layer has finished by now, so the parents can be read without worrying about the other threads
+    let mut path = vec![exit];
+    while *path.last().unwrap() != grid.entrance_location {
+        let parent = parents[index(*path.last().unwrap())].load(Ordering::Relaxed);
+        path.push(grid.coordinate_of(parent));
+    }
+    path.reverse();
+    stats.path_length = path.len();
+    stats.elapsed = start_time.elapsed();
+    let cost = path_cost(grid, &path, connectivity);
+    (Some(Solution { path, exit, cost }), stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_maze, solve::solve_bfs};
+
+    // The threads can pick between equally short paths differently from run to run, so it's the length that has to match
+    #[test]
+    fn finds_a_path_as_short_as_bfs() {
+        for name in ["maze-Medium", "maze-Large"] {
+            let grid = load_maze(name);
+            let (solution, stats) = solve_parallel(&grid, Connectivity::Four);
+            let solution = solution.unwrap();
+            let (bfs, bfs_stats) = solve_bfs(&mut grid.clone());
+            assert_eq!(solution.path.len(), bfs.unwrap().path.len(), "{}", name);
+            assert_eq!(stats.path_length, bfs_stats.path_length, "{}", name);
+            assert_eq!(grid.validate_path(&solution.path, Connectivity::Four), Ok(solution.cost), "{}", name);
+        }
+    }
+
+    #[test]
+    fn a_sealed_exit_has_no_path() {
+        let grid: Grid<Cell> = "#####\n--#--\n#####".parse().unwrap();
+        assert_eq!(solve_parallel(&grid, Connectivity::Four).0, None);
+    }
+}
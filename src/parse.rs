@@ -0,0 +1,668 @@
+use std::{collections::HashMap, fmt, fs::File, io::{self, BufRead, BufReader, Read}, path::Path, str::FromStr};
+
+use log::{debug, info};
+
+use crate::grid::{Cell, CellType, Coordinate, Direction, Grid, NeighbourOrder, MAX_SIDE};
+
+// Everything that can go wrong while loading a maze
+pub enum MazeError {
+    Io(io::Error),
+    MissingEntrance,
+    MissingExit,
+    // The entrance is also listed as an exit, which would make every maze solved before it started
+    EntranceIsExit(Coordinate),
+    EmptyFile,
+    RaggedRows { row: usize, expected: usize, found: usize },
+    // Portals come in pairs, so every portal letter has to appear exactly twice
+    UnpairedPortal { letter: char, count: usize },
+    // Cells on the border that are open but aren't the entrance or the exit, found by Grid::check_border
+    OpenBorder(Vec<Coordinate>),
+    // A maze can only have one 'S' marking its entrance and one 'G' marking its goal
+    DuplicateMarker { marker: char, count: usize },
+    // Grid::set_openings was asked to put an entrance or exit somewhere it can't go
+    OutOfBounds(Coordinate),
+    InWall(Coordinate),
+    // A JSON or CSV maze that can't be read, with what was wrong with it
+    Malformed(String),
+    // More than MAX_SIDE cells across or down, too far for a Coordinate to reach
+    TooLarge { width: usize, height: usize },
+}
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MazeError::Io(error) => write!(f, "could not read maze: {}", error),
+            MazeError::MissingEntrance => write!(f, "maze has no entrance (no '-' on its border and no 'S')"),
+            MazeError::MissingExit => write!(f, "maze has no exit (only one '-' on its border and no 'G')"),
+            MazeError::EntranceIsExit(coordinate) => write!(f, "maze entrance at {} is also its exit", coordinate),
+            MazeError::EmptyFile => write!(f, "maze file is empty"),
+            MazeError::RaggedRows { row, expected, found } => write!(f, "maze row {} is {} cells wide, but the first row is {}", row, found, expected),
+            MazeError::UnpairedPortal { letter, count } => write!(f, "portal '{}' appears {} times, but portals need exactly two ends", letter, count),
+            MazeError::OpenBorder(coordinates) => {
+                let coordinates: Vec<String> = coordinates.iter().map(|coordinate| coordinate.to_string()).collect();
+                write!(f, "maze border has gaps that aren't the entrance or exit at {}", coordinates.join(" "))
+            },
+            MazeError::DuplicateMarker { marker, count } => write!(f, "'{}' appears {} times, but a maze can only have one", marker, count),
+            MazeError::OutOfBounds(coordinate) => write!(f, "{} is outside the maze", coordinate),
+            MazeError::InWall(coordinate) => write!(f, "{} is a wall", coordinate),
+            MazeError::Malformed(reason) => write!(f, "could not read maze: {}", reason),
+            MazeError::TooLarge { width, height } => write!(f, "maze is {}x{} cells, but it can't be more than {} either way", width, height, MAX_SIDE),
+        }
+    }
+}
+// main prints a returned error using Debug, so make that the readable message rather than the raw enum
+impl fmt::Debug for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+impl std::error::Error for MazeError {}
+// allow ? to turn IO errors into MazeErrors
+impl From<io::Error> for MazeError {
+    fn from(error: io::Error) -> Self {
+        MazeError::Io(error)
+    }
+}
+
+// The characters a maze file uses for each kind of cell
+// Spaces are always stripped before parsing, so none of these can be a space
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GridConfig {
+    pub wall_char: char,
+    pub path_char: char,
+    // The character that marks the entrance and exits when it's on the border; inside the maze it's a path
+    pub opening_char: char,
+    // Lines starting with this are comments and are skipped entirely
+    // '#' is also a wall, so a prefix of "# " (with the space) is needed to tell a comment from a row starting with a wall
+    pub comment_prefix: Option<String>,
+    // For open fields, mazes with no wall round them: the entrance and the exit, in place of openings on the border
+    // The opening character is then a plain path wherever it is, so the edge of the maze can be open all the way round
+    pub openings: Option<(Coordinate, Coordinate)>,
+}
+// The format of the mazes in mazes/: '#' for walls and '-' for both paths and openings
+impl Default for GridConfig {
+    fn default() -> Self {
+        GridConfig {
+            wall_char: '#',
+            path_char: '-',
+            opening_char: '-',
+            comment_prefix: None,
+            openings: None,
+        }
+    }
+}
+impl GridConfig {
+    // What kind of cell a character is in this format, for Grid::from_string_with
+    // Opening characters are classified as entrances, which from_string_with only treats as openings on the border,
+    // unless the openings are given, when they're paths
+    // The configured characters are checked first, so a format can use a digit, a capital letter or an arrow as one of them;
+    // only the characters it hasn't claimed are weighted terrain, portals and one-way cells (see is_configured)
+    pub fn classify(&self, char: char) -> Option<CellType> {
+        match char {
+            ' ' => None,
+            _ if char == self.opening_char && self.openings.is_none() => Some(CellType::Entrance),
+            _ if char == self.opening_char => Some(CellType::Path),
+            _ if char == self.path_char => Some(CellType::Path),
+            _ if char == self.wall_char => Some(CellType::Wall),
+            '1'..='9' | 'A'..='Z' | '^' | 'v' | '<' | '>' => Some(CellType::Path),
+            _ => None,
+        }
+    }
+
+    // Whether char is one of this format's own wall, path or opening characters, which then mean only that, even if
+    // they'd otherwise be weighted terrain, a portal, a one-way cell or an 'S' or 'G' marker
+    pub(crate) fn is_configured(&self, char: char) -> bool {
+        char == self.wall_char || char == self.path_char || char == self.opening_char
+    }
+}
+
+// The first two bytes of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// Read everything from reader as text, whatever its encoding (see decode), un-gzipping it first if need be
+pub(crate) fn read_maze_text<R: BufRead>(reader: R) -> Result<String, MazeError> {
+    let mut maze_bytes = Vec::new();
+    decompressed(reader)?.read_to_end(&mut maze_bytes)?;
+    Ok(decode(maze_bytes))
+}
+
+// Turn bytes from a maze file into text
+// Maze glyphs are plain ASCII, so a file only has to be UTF-8 if it uses non-ASCII glyphs of its own. Anything that isn't
+// UTF-8 (a Latin-1 comment, say) is read a byte at a time as Latin-1 instead, which keeps every ASCII byte as it was;
+// the other bytes turn into characters that aren't cells, so parsing skips over them like any other stray character
+pub(crate) fn decode(maze_bytes: Vec<u8>) -> String {
+    String::from_utf8(maze_bytes).unwrap_or_else(|error| error.into_bytes().into_iter().map(char::from).collect())
+}
+
+// reader, un-gzipped as it's read if it's gzipped
+// A gzipped maze is spotted by its first bytes rather than its file name, so it works from stdin too
+pub(crate) fn decompressed<'a, R: BufRead + 'a>(mut reader: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        gunzip(reader)
+    }
+    else {
+        Ok(Box::new(reader))
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gunzip<'a, R: BufRead + 'a>(reader: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    Ok(Box::new(BufReader::new(flate2::bufread::GzDecoder::new(reader))))
+}
+
+// Without the gzip feature, say so rather than trying to parse the compressed bytes as a maze
+#[cfg(not(feature = "gzip"))]
+fn gunzip<'a, R: BufRead + 'a>(_reader: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    Err(io::Error::other("maze is gzipped, but mazesolver was built without the gzip feature"))
+}
+
+impl Grid<Cell> {
+    // Grid constructor
+    pub fn new(path_to_maze: &Path) -> Result<Grid<Cell>, MazeError> {
+        Grid::new_with_config(path_to_maze, &GridConfig::default())
+    }
+
+    // Grid constructor for maze files that use their own wall and path characters
+    pub fn new_with_config(path_to_maze: &Path, config: &GridConfig) -> Result<Grid<Cell>, MazeError> {
+        Grid::from_reader_with_config(BufReader::new(File::open(path_to_maze)?), config)
+    }
+
+    // Build a Grid from the text of a maze already in memory
+    pub fn from_string(maze_text: &str) -> Result<Grid<Cell>, MazeError> {
+        Grid::from_string_with_config(maze_text, &GridConfig::default())
+    }
+
+    // Build a Grid from a maze read from anywhere: a file, stdin, or a byte slice
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Grid<Cell>, MazeError> {
+        Grid::from_reader_with_config(reader, &GridConfig::default())
+    }
+
+    pub fn from_reader_with_config<R: BufRead>(reader: R, config: &GridConfig) -> Result<Grid<Cell>, MazeError> {
+        Grid::from_string_with_config(&read_maze_text(reader)?, config)
+    }
+
+    // Build a Grid from the text of a maze, using the characters in config to tell walls from paths
+    pub fn from_string_with_config(maze_text: &str, config: &GridConfig) -> Result<Grid<Cell>, MazeError> {
+        let Some(comment_prefix) = &config.comment_prefix else {
+            return Grid::parse(maze_text, |char| config.classify(char), |char| config.is_configured(char), config.openings);
+        };
+        // Drop the comment lines before parsing, so they don't count as rows
+        let uncommented: Vec<&str> = maze_text.lines().filter(|line| !line.starts_with(comment_prefix.as_str())).collect();
+        Grid::parse(&uncommented.join("\n"), |char| config.classify(char), |char| config.is_configured(char), config.openings)
+    }
+
+    // Build a Grid from a maze read from anywhere, using classify to decide what each character is (see from_string_with)
+    pub fn from_reader_with<R: BufRead, F: Fn(char) -> Option<CellType>>(reader: R, classify: F) -> Result<Grid<Cell>, MazeError> {
+        Grid::from_string_with(&read_maze_text(reader)?, classify)
+    }
+
+    // Build a Grid from the text of a maze, using classify to decide what kind of cell each character is
+    // classify returns None for characters that aren't cells at all (spaces, in the default format), which are skipped over;
+    // Entrance or Exit for opening characters, which become the entrance or an exit on the border and a plain path elsewhere;
+    // and Wall or Path for everything else. A digit from 1 to 9 classified as a path is weighted terrain costing that much,
+    // and a capital letter classified as a path is one end of a portal to the other cell with the same letter,
+    // apart from 'S' and 'G', which mark the entrance and the goal wherever they are, even in the middle of the maze
+    // An arrow ('^', 'v', '<' or '>') classified as a path is one-way: the only step into it or out of it is the way it points
+    pub fn from_string_with<F: Fn(char) -> Option<CellType>>(maze_text: &str, classify: F) -> Result<Grid<Cell>, MazeError> {
+        Grid::parse(maze_text, classify, |_| false, None)
+    }
+
+    // from_string_with, but with the entrance and exit given as openings (for open fields) rather than found in the maze,
+    // and with plain picking out characters that are only ever what classify says, never terrain, portals, arrows or markers
+    // Every other constructor ends up here, so this is the one place the maze format is parsed
+    // Whatever text it's given, it returns either a Grid or a MazeError and never panics (fuzz/fuzz_targets/parse.rs checks this)
+    fn parse<F: Fn(char) -> Option<CellType>, P: Fn(char) -> bool>(maze_text: &str, classify: F, plain: P, openings: Option<(Coordinate, Coordinate)>) -> Result<Grid<Cell>, MazeError> {
+        // Classify every character, one row per line, leaving out the ones that aren't cells
+        let mut maze_as_vec: Vec<Vec<(char, CellType)>> = maze_text.lines()
+            .map(|line| line.chars().filter_map(|char| classify(char).map(|cell_type| (char, cell_type))).collect())
+            .collect();
+        // Blank lines before and after the maze don't count as rows
+        while maze_as_vec.last().is_some_and(|row| row.is_empty()) {
+            maze_as_vec.pop();
+        }
+        let leading_blank_lines = maze_as_vec.iter().take_while(|row| row.is_empty()).count();
+        maze_as_vec.drain(..leading_blank_lines);
+        // Get the width and height of the maze; blank lines were trimmed above, so any first row has at least one cell in it
+        let Some(first_row) = maze_as_vec.first() else {
+            return Err(MazeError::EmptyFile);
+        };
+        let width = first_row.len();
+        let height = maze_as_vec.len();
+        if width > MAX_SIDE || height > MAX_SIDE {
+            return Err(MazeError::TooLarge { width, height });
+        }
+        // Every row has to be as wide as the first, otherwise our index maths breaks
+        if let Some((row, cells)) = maze_as_vec.iter().enumerate().find(|(_, cells)| cells.len() != width) {
+            return Err(MazeError::RaggedRows { row, expected: width, found: cells.len() });
+        }
+        // Declare vectors to hold all the cells and a vector of coordinates to hold where the exits are
+        let mut cells = Vec::with_capacity(width * height);
+        let mut exit_coordinates = Vec::new();
+        // Boolean to check if we've found the entrance yet
+        let mut entrance_found = false;
+        // Where each portal letter appears, to pair them up once we've seen the whole maze
+        let mut portal_ends: HashMap<char, Vec<Coordinate>> = HashMap::new();
+        // Where any 'S' and 'G' markers are
+        let mut starts = Vec::new();
+        let mut goals = Vec::new();
+        for (row, row_cells) in maze_as_vec.iter().enumerate() {
+            for (column, &(char, cell_type)) in row_cells.iter().enumerate() {
+                let coordinate = Coordinate::new(column, row);
+                match cell_type {
+                    CellType::Entrance | CellType::Exit if row == 0 || row == height - 1 || column == 0 || column == width - 1 => {
+                        // Only the first opening we find is the entrance, the rest are exits
+                        if !entrance_found {
+                            cells.push(Cell::new(coordinate, CellType::Entrance));
+                            entrance_found = true;
+                        }
+                        else {
+                            cells.push(Cell::new(coordinate, CellType::Exit));
+                        }
+                        exit_coordinates.push(coordinate);
+                    },
+                    // Any wall character is a wall
+                    CellType::Wall => {
+                        cells.push(Cell::new(coordinate, CellType::Wall));
+                    },
+                    // If it's a path, or an opening character that's not on the edge, it's a path
+                    _ if plain(char) => {
+                        cells.push(Cell::new(coordinate, CellType::Path));
+                    },
+                    // A digit from 1 to 9 is weighted terrain: a path that costs that much to step into
+                    _ => {
+                        let mut cell = Cell::new(coordinate, CellType::Path);
+                        if let Some(weight) = char.to_digit(10).filter(|&digit| digit > 0) {
+                            cell.weight = weight as usize;
+                        }
+                        cell.direction = Direction::from_arrow(char);
+                        // A capital letter is a portal: an ordinary path that also leads to the other cell with the same letter
+                        match char {
+                            'S' => starts.push(coordinate),
+                            'G' => goals.push(coordinate),
+                            _ if char.is_ascii_uppercase() => portal_ends.entry(char).or_default().push(coordinate),
+                            _ => (),
+                        }
+                        cells.push(cell);
+                    },
+                }
+            }
+        };
+        debug!("openings on the border: {:?}", exit_coordinates);
+        // Get the entrance and exit coordinates
+        // The first opening is the entrance and every other opening is an exit, so imperfect mazes with several exits work too
+        // An 'S' or a 'G' takes the place of the openings: with an 'S' every opening is an exit, and with a 'G' it's the only exit
+        let marker = |marker: char, coordinates: Vec<Coordinate>| match coordinates[..] {
+            [] => Ok(None),
+            [coordinate] => Ok(Some(coordinate)),
+            _ => Err(MazeError::DuplicateMarker { marker, count: coordinates.len() }),
+        };
+        let (start, goal) = (marker('S', starts)?, marker('G', goals)?);
+        // Given openings take the place of everything else, so they have to be somewhere they can go
+        if let Some((entrance, exit)) = openings {
+            for coordinate in [entrance, exit] {
+                match maze_as_vec.get(coordinate.y as usize).and_then(|row| row.get(coordinate.x as usize)) {
+                    None => return Err(MazeError::OutOfBounds(coordinate)),
+                    Some((_, CellType::Wall)) => return Err(MazeError::InWall(coordinate)),
+                    Some(_) => (),
+                }
+            }
+        }
+        let (start, goal) = match openings {
+            Some((entrance, exit)) => (Some(entrance), Some(exit)),
+            None => (start, goal),
+        };
+        let mut border_openings = exit_coordinates.iter().copied();
+        let entrance_location = match start {
+            Some(start) => start,
+            None => border_openings.next().ok_or(MazeError::MissingEntrance)?,
+        };
+        let exits: Vec<Coordinate> = match goal {
+            Some(goal) => vec![goal],
+            None => border_openings.collect(),
+        };
+        if exits.is_empty() {
+            return Err(MazeError::MissingExit);
+        }
+        // Every opening is a different cell, so this can't happen with the entrance and exits found above;
+        // the check is here so the grid never gets built with its search already over, however the openings are chosen
+        if exits.contains(&entrance_location) {
+            return Err(MazeError::EntranceIsExit(entrance_location));
+        }
+        // The cells were typed as the openings were found, so retype any an 'S' or 'G' has overruled
+        for &coordinate in exit_coordinates.iter().chain(&start).chain(&goal) {
+            cells[coordinate.y as usize * width + coordinate.x as usize].cell_type = if coordinate == entrance_location {
+                CellType::Entrance
+            }
+            else if exits.contains(&coordinate) {
+                CellType::Exit
+            }
+            else {
+                CellType::Path
+            };
+        }
+        // Link the two ends of each portal to each other
+        let mut portals = HashMap::new();
+        for (letter, ends) in portal_ends {
+            let [first, second] = ends[..] else {
+                return Err(MazeError::UnpairedPortal { letter, count: ends.len() });
+            };
+            portals.insert(first, second);
+            portals.insert(second, first);
+        }
+        info!("Grid constructed ({}x{})", width, height);
+        Ok(Grid {
+            width,
+            height,
+            entrance_location,
+            exits,
+            portals,
+            cells,
+            cost_x: 1,
+            cost_y: 1,
+            toroidal: false,
+            neighbour_order: NeighbourOrder::default(),
+        })
+    }
+
+    // Put the entrance, the exit, or both somewhere other than where the maze file's openings put them
+    // Giving an exit replaces every exit the maze had with that one; giving only an entrance keeps the other exits,
+    // apart from the new entrance if it was one of them. Openings that are neither any more become plain paths
+    // Errors if either is outside the grid or a wall, or if that leaves the maze without an exit
+    pub fn set_openings(&mut self, entrance: Option<Coordinate>, exit: Option<Coordinate>) -> Result<(), MazeError> {
+        for coordinate in [entrance, exit].into_iter().flatten() {
+            match self.try_cell_at(coordinate) {
+                None => return Err(MazeError::OutOfBounds(coordinate)),
+                Some(cell) if cell.cell_type == CellType::Wall => return Err(MazeError::InWall(coordinate)),
+                Some(_) => (),
+            }
+        }
+        let entrance = entrance.unwrap_or(self.entrance_location);
+        let exits: Vec<Coordinate> = match exit {
+            Some(exit) => vec![exit],
+            None => self.exits.iter().copied().filter(|&exit| exit != entrance).collect(),
+        };
+        if exits.is_empty() {
+            return Err(MazeError::MissingExit);
+        }
+        if exits.contains(&entrance) {
+            return Err(MazeError::EntranceIsExit(entrance));
+        }
+        let old_openings: Vec<Coordinate> = [self.entrance_location].into_iter().chain(self.exits.iter().copied()).collect();
+        for opening in old_openings {
+            self.cell_at_mut(opening).cell_type = CellType::Path;
+        }
+        self.cell_at_mut(entrance).cell_type = CellType::Entrance;
+        for &exit in &exits {
+            self.cell_at_mut(exit).cell_type = CellType::Exit;
+        }
+        self.entrance_location = entrance;
+        self.exits = exits;
+        Ok(())
+    }
+
+    // Check that the border is walled all the way round apart from the entrance and one exit
+    // Every opening character on the border is an exit unless a 'G' or set_openings chose the exit, so a stray gap in an
+    // ordinary maze is an extra exit: any exit after the first counts as a gap. So does a path or weighted cell on the border,
+    // which isn't an opening at all. Errors with every gap, so a malformed file can be fixed in one go
+    pub fn check_border(&self) -> Result<(), MazeError> {
+        let gaps: Vec<Coordinate> = self.cells.iter()
+            .filter(|cell| self.on_border(cell.coordinate))
+            .filter(|cell| cell.cell_type == CellType::Path || (cell.cell_type == CellType::Exit && cell.coordinate != self.exits[0]))
+            .map(|cell| cell.coordinate)
+            .collect();
+        if gaps.is_empty() {
+            Ok(())
+        }
+        else {
+            Err(MazeError::OpenBorder(gaps))
+        }
+    }
+}
+
+// allow "###\n- -\n###".parse::<Grid<Cell>>() for mazes held in strings
+impl FromStr for Grid<Cell> {
+    type Err = MazeError;
+
+    fn from_str(maze_text: &str) -> Result<Self, Self::Err> {
+        Grid::from_string(maze_text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitgrid::BitGrid, solve::{solve, Connectivity, SolveOutcome}};
+
+    #[test]
+    fn configured_characters_are_never_portals_terrain_or_arrows() {
+        let config = GridConfig { wall_char: 'v', path_char: 'O', opening_char: '1', ..GridConfig::default() };
+        let grid = Grid::from_string_with_config("vvvvv\n1OOO1\nvvvvv", &config).unwrap();
+        assert!(grid.portals.is_empty());
+        assert!(grid.cells.iter().all(|cell| cell.weight == 1 && cell.direction.is_none()));
+        assert_eq!(grid[Coordinate { x: 0, y: 0 }].cell_type, CellType::Wall);
+        assert_eq!((grid.entrance(), grid.exit()), (Coordinate { x: 0, y: 1 }, Coordinate { x: 4, y: 1 }));
+    }
+
+    #[test]
+    fn unconfigured_characters_keep_their_meanings() {
+        let config = GridConfig { path_char: '.', ..GridConfig::default() };
+        let grid = Grid::from_string_with_config("#######\n-.A3>A-\n#######", &config).unwrap();
+        assert_eq!(grid.portals.get(&Coordinate { x: 2, y: 1 }), Some(&Coordinate { x: 5, y: 1 }));
+        assert_eq!(grid[Coordinate { x: 3, y: 1 }].weight, 3);
+        assert_eq!(grid[Coordinate { x: 4, y: 1 }].direction, Some(Direction::East));
+    }
+
+    #[test]
+    fn a_short_row_is_ragged() {
+        let error = Grid::from_string("#-#\n#-\n#-#").unwrap_err();
+        assert!(matches!(error, MazeError::RaggedRows { row: 1, expected: 3, found: 2 }), "{}", error);
+    }
+
+    #[test]
+    fn a_long_row_is_ragged() {
+        let error = Grid::from_string("#-#\n#-#\n#-##").unwrap_err();
+        assert!(matches!(error, MazeError::RaggedRows { row: 2, expected: 3, found: 4 }), "{}", error);
+    }
+
+    #[test]
+    fn an_empty_file_is_an_error() {
+        for maze_text in ["", "\n\n", "   \n"] {
+            assert!(matches!(Grid::from_string(maze_text), Err(MazeError::EmptyFile)), "{:?}", maze_text);
+        }
+    }
+
+    #[test]
+    fn from_reader_reads_the_same_maze_as_new() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/mazes/maze-Medium.txt");
+        let from_file = Grid::new(Path::new(path)).unwrap();
+        let from_reader = Grid::from_reader(BufReader::new(File::open(path).unwrap())).unwrap();
+        assert_eq!(from_reader, from_file);
+        let from_bytes = Grid::from_reader(&b"# - #\n# - #\n# - #\n"[..]).unwrap();
+        assert_eq!((from_bytes.entrance(), from_bytes.exit()), (Coordinate { x: 1, y: 0 }, Coordinate { x: 1, y: 2 }));
+    }
+
+    #[test]
+    fn mazes_parse_from_strings() {
+        let grid: Grid<Cell> = "# # # #\n- - - #\n# # - -\n# # # #".parse().unwrap();
+        assert_eq!((grid.width(), grid.height()), (4, 4));
+        assert_eq!((grid.entrance(), grid.exits()), (Coordinate { x: 0, y: 1 }, &[Coordinate { x: 3, y: 2 }][..]));
+        assert_eq!(grid[Coordinate { x: 2, y: 1 }].cell_type, CellType::Path);
+        assert_eq!(grid[Coordinate { x: 0, y: 2 }].cell_type, CellType::Wall);
+        let text = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/mazes/maze-Easy.txt")).unwrap();
+        assert_eq!(text.parse::<Grid<Cell>>().unwrap(), Grid::new(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/mazes/maze-Easy.txt"))).unwrap());
+    }
+
+    #[test]
+    fn a_maze_with_one_opening_has_no_exit() {
+        assert!(matches!(Grid::from_string("#####\n----#\n#####"), Err(MazeError::MissingExit)));
+    }
+
+    #[test]
+    fn a_maze_with_no_openings_has_no_entrance() {
+        assert!(matches!(Grid::from_string("#####\n#---#\n#####"), Err(MazeError::MissingEntrance)));
+    }
+
+    #[test]
+    fn an_entrance_given_as_the_exit_is_an_error() {
+        let same = Coordinate { x: 2, y: 1 };
+        let config = GridConfig { openings: Some((same, same)), ..GridConfig::default() };
+        assert!(matches!(Grid::from_string_with_config("#####\n#---#\n#####", &config), Err(MazeError::EntranceIsExit(coordinate)) if coordinate == same));
+        let mut grid = Grid::from_string("#####\n-----\n#####").unwrap();
+        assert!(matches!(grid.set_openings(Some(same), Some(same)), Err(MazeError::EntranceIsExit(coordinate)) if coordinate == same));
+    }
+
+    // "# " rather than "#", so the header is a comment but a row of wall starting with '#' isn't
+    #[test]
+    fn comment_lines_and_trailing_blank_lines_are_skipped() {
+        let config = GridConfig { comment_prefix: Some("# ".to_string()), ..GridConfig::default() };
+        let maze_text = "# generated by foo\n#####\n-----\n#####\n\n\n";
+        let grid = Grid::from_string_with_config(maze_text, &config).unwrap();
+        assert_eq!(grid, Grid::from_string("#####\n-----\n#####").unwrap());
+        let bitgrid = BitGrid::from_reader_with_config(maze_text.as_bytes(), &config).unwrap();
+        assert_eq!((bitgrid.width(), bitgrid.height()), (5, 3));
+    }
+
+    // With the cells spaced out, wall rows start "# " too, so a different prefix is needed
+    #[test]
+    fn a_prefix_unlike_the_wall_leaves_spaced_rows_alone() {
+        let config = GridConfig { comment_prefix: Some("//".to_string()), ..GridConfig::default() };
+        let grid = Grid::from_string_with_config("// header\n# # #\n- - -\n// between rows\n# # #", &config).unwrap();
+        assert_eq!(grid, Grid::from_string("# # #\n- - -\n# # #").unwrap());
+    }
+
+    // Without a prefix nothing's a comment, so a line of text in the maze breaks it rather than being quietly dropped
+    #[test]
+    fn without_a_prefix_nothing_is_a_comment() {
+        let grid = Grid::from_string("#####\n-----\n#####\n\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (5, 3));
+        assert!(Grid::from_string("# generated by foo\n#####\n-----\n#####").is_err());
+    }
+
+    // 0xE9 is 'é' in Latin-1, and on its own isn't UTF-8 at all
+    #[test]
+    fn a_stray_byte_that_isnt_utf8_is_skipped() {
+        let config = GridConfig { comment_prefix: Some("//".to_string()), ..GridConfig::default() };
+        let maze_bytes = b"// caf\xe9 maze\n#####\n-----\xe9\n#####\n";
+        let path = std::env::temp_dir().join(format!("mazesolver-latin1-{}.txt", std::process::id()));
+        std::fs::write(&path, maze_bytes).unwrap();
+        let grid = Grid::new_with_config(&path, &config);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(grid.unwrap(), Grid::from_string("#####\n-----\n#####").unwrap());
+        let bitgrid = BitGrid::from_reader_with_config(&maze_bytes[..], &config).unwrap();
+        assert_eq!((bitgrid.width(), bitgrid.height()), (5, 3));
+    }
+
+    #[test]
+    fn a_border_walled_apart_from_its_openings_is_clean() {
+        let grid = Grid::new(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/mazes/maze-Medium.txt"))).unwrap();
+        assert!(grid.check_border().is_ok());
+    }
+
+    // The markers put the entrance and exit inside, so the gaps in the border aren't openings any more;
+    // terrain isn't an opening character either, so a '3' in the border is a gap too
+    #[test]
+    fn gaps_in_the_border_that_arent_openings_are_listed() {
+        let grid: Grid<Cell> = "##3##\n-S--#\n#-#-#\n#--G-\n#####".parse().unwrap();
+        let gaps = vec![Coordinate { x: 2, y: 0 }, Coordinate { x: 0, y: 1 }, Coordinate { x: 4, y: 3 }];
+        assert!(matches!(grid.check_border(), Err(MazeError::OpenBorder(coordinates)) if coordinates == gaps));
+    }
+
+    // Without markers the stray gap is read as a second exit, one step from the entrance
+    #[test]
+    fn a_stray_gap_in_an_ordinary_maze_is_listed() {
+        let grid: Grid<Cell> = "#####\n--#--\n----#\n#-###\n#####".parse().unwrap();
+        assert_eq!(grid.exits, [Coordinate { x: 4, y: 1 }, Coordinate { x: 0, y: 2 }]);
+        assert!(matches!(grid.check_border(), Err(MazeError::OpenBorder(coordinates)) if coordinates == [Coordinate { x: 0, y: 2 }]));
+        // Choosing the exit leaves the gap a plain path, which is still a gap
+        let mut chosen = grid.clone();
+        chosen.set_openings(None, Some(Coordinate { x: 4, y: 1 })).unwrap();
+        assert!(matches!(chosen.check_border(), Err(MazeError::OpenBorder(coordinates)) if coordinates == [Coordinate { x: 0, y: 2 }]));
+    }
+
+    #[test]
+    fn swapping_the_openings_reverses_the_path() {
+        let mut grid = Grid::new(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/mazes/maze-Easy.txt"))).unwrap();
+        let (entrance, exit) = (grid.entrance(), grid.exit());
+        let SolveOutcome::Found { path, .. } = solve(&mut grid) else {
+            panic!("maze-Easy has no solution");
+        };
+        grid.set_openings(Some(exit), Some(entrance)).unwrap();
+        assert_eq!((grid.entrance(), grid.exits()), (exit, &[entrance][..]));
+        let SolveOutcome::Found { path: swapped_path, .. } = solve(&mut grid) else {
+            panic!("maze-Easy has no solution backwards");
+        };
+        assert_eq!(swapped_path, path.into_iter().rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn openings_have_to_be_open_cells_inside_the_maze() {
+        let mut grid = Grid::from_string("#####\n-----\n#####").unwrap();
+        assert!(matches!(grid.set_openings(Some(Coordinate { x: 2, y: 0 }), None), Err(MazeError::InWall(_))));
+        assert!(matches!(grid.set_openings(None, Some(Coordinate { x: 5, y: 1 })), Err(MazeError::OutOfBounds(_))));
+    }
+
+    // The border gaps are ignored once S and G say where to start and finish
+    #[test]
+    fn s_and_g_in_the_middle_are_the_entrance_and_exit() {
+        let mut grid = Grid::from_string("#######\n-----##\n#-#S#-#\n#-#-#-#\n#---G--\n#######").unwrap();
+        assert_eq!((grid.entrance(), grid.exits()), (Coordinate { x: 3, y: 2 }, &[Coordinate { x: 4, y: 4 }][..]));
+        assert_eq!(grid[Coordinate { x: 0, y: 1 }].cell_type, CellType::Path);
+        let SolveOutcome::Found { path, .. } = solve(&mut grid) else {
+            panic!("no way from S to G");
+        };
+        assert_eq!(path, [(3, 2), (3, 3), (3, 4), (4, 4)].map(|(x, y)| Coordinate { x, y }));
+    }
+
+    #[test]
+    fn s_alone_keeps_the_border_gaps_as_exits() {
+        let grid = Grid::from_string("#####\n-S--#\n###-#").unwrap();
+        assert_eq!((grid.entrance(), grid.exits()), (Coordinate { x: 1, y: 1 }, &[Coordinate { x: 0, y: 1 }, Coordinate { x: 3, y: 2 }][..]));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn a_gzipped_maze_solves_like_the_plain_one() {
+        use std::io::Write;
+        let plain_path = concat!(env!("CARGO_MANIFEST_DIR"), "/mazes/maze-Medium.txt");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&std::fs::read(plain_path).unwrap()).unwrap();
+        let gzipped_path = std::env::temp_dir().join(format!("mazesolver-gzip-{}.txt.gz", std::process::id()));
+        std::fs::write(&gzipped_path, encoder.finish().unwrap()).unwrap();
+        let gzipped = Grid::new(&gzipped_path);
+        std::fs::remove_file(&gzipped_path).unwrap();
+        let (mut gzipped, mut plain) = (gzipped.unwrap(), Grid::new(Path::new(plain_path)).unwrap());
+        assert_eq!(gzipped, plain);
+        let (SolveOutcome::Found { path: gzipped_path, .. }, SolveOutcome::Found { path: plain_path, .. }) = (solve(&mut gzipped), solve(&mut plain)) else {
+            panic!("maze-Medium has no solution");
+        };
+        assert_eq!(gzipped_path, plain_path);
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn a_gzipped_maze_needs_the_gzip_feature() {
+        let error = Grid::from_reader(&[0x1f, 0x8b, 0x08, 0x00][..]).unwrap_err();
+        assert!(error.to_string().contains("gzip feature"), "{}", error);
+    }
+
+    // With no wall round it every edge cell would be an opening, so the entrance and exit are given; the search keeps to the
+    // field by bounds alone, so the shortest way across is the straight-line distance, and round the wall it hugs the edge
+    #[test]
+    fn an_open_field_solves_between_the_given_openings() {
+        let config = GridConfig { openings: Some((Coordinate { x: 0, y: 5 }, Coordinate { x: 9, y: 0 })), ..GridConfig::default() };
+        let field = Grid::from_string_with_config(&["----------"; 6].join("\n"), &config).unwrap();
+        assert_eq!((field.entrance(), field.exits.clone()), (Coordinate { x: 0, y: 5 }, vec![Coordinate { x: 9, y: 0 }]));
+        let SolveOutcome::Found { path, cost, .. } = solve(&mut field.clone()) else {
+            panic!("no way across the open field");
+        };
+        assert_eq!(cost, 14);
+        assert_eq!(field.validate_path(&path, Connectivity::Four), Ok(14));
+        let walled = Grid::from_string_with_config("----------\n----------\n#########-\n----------", &GridConfig {
+            openings: Some((Coordinate { x: 0, y: 3 }, Coordinate { x: 0, y: 0 })),
+            ..GridConfig::default()
+        }).unwrap();
+        let SolveOutcome::Found { cost, .. } = solve(&mut walled.clone()) else {
+            panic!("no way round the wall");
+        };
+        assert_eq!(cost, 9 + 3 + 9);
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::grid::{Cell, CellType, Coordinate, Grid};
+
+// Draw the maze as text, one character per cell: '#' for walls, ' ' for open cells, 'S' for the entrance and 'E' for every exit,
+// and path_marker for every other cell on the path
+// path_marker is a string rather than a char so it can carry ANSI colour codes around the marker
+pub fn render(grid: &Grid<Cell>, path: &[Coordinate], path_marker: &str) -> String {
+    render_visited(grid, path, &HashSet::new(), path_marker)
+}
+
+// Draw the maze as render does, but also shade every cell the search visited that didn't end up on the path with '.'
+// This shows how the search spread out: BFS floods outwards in every direction, while A* heads for the exit
+pub fn render_visited(grid: &Grid<Cell>, path: &[Coordinate], visited: &HashSet<Coordinate>, path_marker: &str) -> String {
+    render_search(grid, path, visited, &HashSet::new(), path_marker)
+}
+
+// Draw the maze as render_visited does, with the cells still waiting in the search's open set marked 'o'
+// Used to draw each frame of an animated search, where there's no path yet
+pub fn render_search(grid: &Grid<Cell>, path: &[Coordinate], visited: &HashSet<Coordinate>, frontier: &HashSet<Coordinate>,
+                     path_marker: &str) -> String {
+    draw(grid, path, visited, frontier, |shade| match shade {
+        Shade::Entrance => "S",
+        Shade::Exit => "E",
+        Shade::Path(_) => path_marker,
+        Shade::Frontier => "o",
+        Shade::Visited => ".",
+        Shade::Wall => "#",
+        Shade::Open => " ",
+    })
+}
+
+// ANSI colour codes for each shade of cell, and the reset code to go back to the terminal's own colours
+const WALL_COLOUR: &str = "\x1b[90m";
+const PATH_COLOUR: &str = "\x1b[32m";
+const VISITED_COLOUR: &str = "\x1b[2;34m";
+const ENTRANCE_COLOUR: &str = "\x1b[36m";
+const EXIT_COLOUR: &str = "\x1b[35m";
+const RESET: &str = "\x1b[0m";
+
+// Draw the maze as render_visited does, but in colour for a terminal, with a legend line above it:
+// grey walls, a green path, faint blue visited cells, a cyan entrance and magenta exits
+// Callers should check the output is a terminal first, since anything else will get the escape codes as text
+pub fn render_colored(grid: &Grid<Cell>, path: &[Coordinate], visited: &HashSet<Coordinate>) -> String {
+    let legend = format!(
+        "{ENTRANCE_COLOUR}S{RESET} entrance  {EXIT_COLOUR}E{RESET} exit  {PATH_COLOUR}*{RESET} path  {VISITED_COLOUR}.{RESET} visited  {WALL_COLOUR}#{RESET} wall\n"
+    );
+    let entrance = format!("{ENTRANCE_COLOUR}S{RESET}");
+    let exit = format!("{EXIT_COLOUR}E{RESET}");
+    let path_marker = format!("{PATH_COLOUR}*{RESET}");
+    let visited_marker = format!("{VISITED_COLOUR}.{RESET}");
+    let wall = format!("{WALL_COLOUR}#{RESET}");
+    legend + &draw(grid, path, visited, &HashSet::new(), |shade| match shade {
+        Shade::Entrance => &entrance,
+        Shade::Exit => &exit,
+        Shade::Path(_) => &path_marker,
+        Shade::Visited => &visited_marker,
+        Shade::Wall => &wall,
+        Shade::Frontier | Shade::Open => " ",
+    })
+}
+
+// Background colours for the heatmap's walls, and for open cells the entrance can't reach
+const HEATMAP_WALL: (u8, u8, u8) = (30, 30, 30);
+const HEATMAP_UNREACHABLE: (u8, u8, u8) = (110, 40, 110);
+
+// Draw the maze with every open cell's background coloured by its distance from the entrance, for a terminal with 24-bit colour
+// distances is the cheapest cost to each cell (from distance_field, say); the nearest cells are blue, shading through cyan,
+// green and yellow to red for the furthest. Walls are near black, and open cells missing from distances (walled off from the
+// entrance) are purple. The entrance and exits are marked 'S' and 'E' over their own colour, and each cell is one column wide
+pub fn render_heatmap(grid: &Grid<Cell>, distances: &HashMap<Coordinate, usize>) -> String {
+    let furthest = distances.values().copied().max().unwrap_or_default();
+    let mut rendered = String::new();
+    for row in grid.rows() {
+        for cell in row {
+            let ((red, green, blue), glyph) = if cell.cell_type == CellType::Wall {
+                (HEATMAP_WALL, ' ')
+            }
+            else {
+                let colour = distances.get(&cell.coordinate).map_or(HEATMAP_UNREACHABLE, |&distance| heat_colour(distance, furthest));
+                let glyph = if cell.coordinate == grid.entrance_location {
+                    'S'
+                }
+                else if grid.exits.contains(&cell.coordinate) {
+                    'E'
+                }
+                else {
+                    ' '
+                };
+                (colour, glyph)
+            };
+            // Black text, so the markers show up on every colour
+            rendered.push_str(&format!("\x1b[30;48;2;{};{};{}m{}", red, green, blue, glyph));
+        }
+        rendered.push_str(RESET);
+        rendered.push('\n');
+    }
+    rendered
+}
+
+// The colour for a distance out of furthest: blue at 0, then cyan, green and yellow, to red at furthest
+fn heat_colour(distance: usize, furthest: usize) -> (u8, u8, u8) {
+    const STOPS: [(f64, f64, f64); 5] = [(0.0, 0.0, 255.0), (0.0, 255.0, 255.0), (0.0, 255.0, 0.0), (255.0, 255.0, 0.0), (255.0, 0.0, 0.0)];
+    // Scaled to between 0 and the number of gaps between stops, so the whole part picks the gap and the rest is how far along it
+    let position = if furthest == 0 { 0.0 } else { distance.min(furthest) as f64 / furthest as f64 * (STOPS.len() - 1) as f64 };
+    let gap = (position as usize).min(STOPS.len() - 2);
+    let along = position - gap as f64;
+    let ((red_from, green_from, blue_from), (red_to, green_to, blue_to)) = (STOPS[gap], STOPS[gap + 1]);
+    let blend = |from: f64, to: f64| (from + (to - from) * along).round() as u8;
+    (blend(red_from, red_to), blend(green_from, green_to), blend(blue_from, blue_to))
+}
+
+// The digits render_numbered draws along the path
+const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+// Draw the maze as render does, but with each path cell showing how many steps along the path it is, modulo 10,
+// so the route can be followed by eye (the entrance, step 0, is still 'S'). One digit keeps every cell one column wide,
+// so the maze stays lined up however long the path is
+pub fn render_numbered(grid: &Grid<Cell>, path: &[Coordinate]) -> String {
+    draw(grid, path, &HashSet::new(), &HashSet::new(), |shade| match shade {
+        Shade::Entrance => "S",
+        Shade::Exit => "E",
+        Shade::Path(step) => DIGITS[step % 10],
+        Shade::Wall => "#",
+        Shade::Frontier | Shade::Visited | Shade::Open => " ",
+    })
+}
+
+// What a cell should be drawn as; each renderer decides how each of these looks
+enum Shade {
+    Entrance,
+    Exit,
+    // A cell on the path, with how many steps along the path it is
+    Path(usize),
+    Frontier,
+    Visited,
+    Wall,
+    Open,
+}
+
+// Draw every cell of the maze with the glyph for its shade, one line per row
+// Earlier shades win: the entrance and exits are always shown, then the path over the search, then the search over the maze
+fn draw<'a>(grid: &Grid<Cell>, path: &[Coordinate], visited: &HashSet<Coordinate>, frontier: &HashSet<Coordinate>,
+            glyph: impl Fn(Shade) -> &'a str) -> String {
+    let path_steps: HashMap<&Coordinate, usize> = path.iter().enumerate().map(|(step, coordinate)| (coordinate, step)).collect();
+    let mut rendered = String::with_capacity((grid.width + 1) * grid.height);
+    for row in grid.rows() {
+        for cell in row {
+            let shade = if cell.coordinate == grid.entrance_location {
+                Shade::Entrance
+            }
+            else if grid.exits.contains(&cell.coordinate) {
+                Shade::Exit
+            }
+            else if let Some(&step) = path_steps.get(&cell.coordinate) {
+                Shade::Path(step)
+            }
+            else if frontier.contains(&cell.coordinate) {
+                Shade::Frontier
+            }
+            else if visited.contains(&cell.coordinate) {
+                Shade::Visited
+            }
+            else if cell.cell_type == CellType::Wall {
+                Shade::Wall
+            }
+            else {
+                Shade::Open
+            };
+            rendered.push_str(glyph(shade));
+        }
+        rendered.push('\n');
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{distance_field::distance_field, solve::Connectivity};
+
+    // Along a corridor the entrance is the coolest colour and the exit, furthest away, the hottest; the pocket below can't be reached
+    #[test]
+    fn the_entrance_is_blue_and_the_furthest_cell_red() {
+        let grid: Grid<Cell> = "#####\n-----\n#####\n##-##\n#####".parse().unwrap();
+        let rendered = render_heatmap(&grid, &distance_field(&grid, grid.entrance_location, Connectivity::Four));
+        let rows: Vec<&str> = rendered.lines().collect();
+        assert!(rows[1].starts_with("\x1b[30;48;2;0;0;255mS"));
+        assert!(rows[1].ends_with(&format!("\x1b[30;48;2;255;0;0mE{}", RESET)));
+        assert!(rows[3].contains("\x1b[30;48;2;110;40;110m "));
+        assert_eq!(heat_colour(0, 4), (0, 0, 255));
+        assert_eq!(heat_colour(2, 4), (0, 255, 0));
+    }
+}
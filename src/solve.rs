@@ -0,0 +1,1196 @@
+use std::{collections::{BinaryHeap, HashMap, HashSet, VecDeque}, fmt, str::FromStr, time::{Duration, Instant}};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, ida_star::solve_ida_star, jps::{is_uniform_cost, solve_jps}};
+
+// The search strategies the solver knows about
+// They all share neighbour generation and path reconstruction; only the open set and its ordering differ
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Algorithm {
+    AStar,
+    // A* with the heuristic multiplied by a weight of at least 1, which heads for the exit more eagerly
+    // It expands fewer cells, and the path it returns costs at most weight times as much as the shortest one
+    WeightedAStar(f64),
+    Dijkstra,
+    Bfs,
+    Dfs,
+    Greedy,
+    // A* that jumps along straight lines instead of expanding every cell; falls back to A* on weighted mazes
+    Jps,
+    // A* as a depth-first search with a rising f-score bound, holding only the current route in memory instead of every cell seen
+    IdaStar,
+}
+impl Algorithm {
+    // The value a cell is ordered on in the open set, given its cost so far and its heuristic distance to the exit
+    // BFS and DFS don't order their open sets at all, so for them this is just the cost
+    fn priority(&self, cost: usize, heuristic: usize) -> usize {
+        match self {
+            Algorithm::AStar | Algorithm::Jps | Algorithm::IdaStar => cost + heuristic,
+            Algorithm::WeightedAStar(weight) => cost + (weight * heuristic as f64).round() as usize,
+            Algorithm::Dijkstra | Algorithm::Bfs | Algorithm::Dfs => cost,
+            Algorithm::Greedy => heuristic,
+        }
+    }
+}
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "astar" => Ok(Algorithm::AStar),
+            "dijkstra" => Ok(Algorithm::Dijkstra),
+            "bfs" => Ok(Algorithm::Bfs),
+            "dfs" => Ok(Algorithm::Dfs),
+            "greedy" => Ok(Algorithm::Greedy),
+            "jps" => Ok(Algorithm::Jps),
+            "idastar" => Ok(Algorithm::IdaStar),
+            _ => Err(format!("unknown algorithm '{}' (expected astar, dijkstra, bfs, dfs, greedy, jps or idastar)", name)),
+        }
+    }
+}
+
+// A route through the maze, from the entrance to whichever exit was reached first
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Solution {
+    // Every coordinate along the route, entrance first and exit last
+    pub path: Vec<Coordinate>,
+    // The exit the route ends at; on a maze with several exits this is the cheapest one to reach
+    pub exit: Coordinate,
+    // What the route costs, in the same units as the search's step costs, counting the weight of every cell stepped into
+    // The entrance is never stepped into, so on an unweighted four-way maze this is one less than the number of cells on the path
+    pub cost: usize,
+}
+
+// How a search turned out, for callers that need to branch on whether there's a way through at all
+// Found carries the route, entrance first, with the exit it ends at and what it costs; either way the search's statistics come along
+// Partial is a search that stopped before it could tell, with the route to the closest cell it got to and why it stopped
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SolveOutcome {
+    Found { path: Vec<Coordinate>, exit: Coordinate, cost: usize, stats: SearchStats },
+    Unreachable { stats: SearchStats },
+    Partial { path: Vec<Coordinate>, cost: usize, reason: SearchError, stats: SearchStats },
+}
+impl SolveOutcome {
+    pub fn is_solved(&self) -> bool {
+        matches!(self, SolveOutcome::Found { .. })
+    }
+
+    pub fn stats(&self) -> &SearchStats {
+        match self {
+            SolveOutcome::Found { stats, .. } | SolveOutcome::Unreachable { stats } | SolveOutcome::Partial { stats, .. } => stats,
+        }
+    }
+}
+// The other solve functions return the solution and the statistics separately; this puts them together
+impl From<(Option<Solution>, SearchStats)> for SolveOutcome {
+    fn from((solution, stats): (Option<Solution>, SearchStats)) -> Self {
+        match solution {
+            Some(Solution { path, exit, cost }) => SolveOutcome::Found { path, exit, cost, stats },
+            None => SolveOutcome::Unreachable { stats },
+        }
+    }
+}
+
+// Measurements from a single search, for comparing how much work each algorithm does
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchStats {
+    // How many cells were popped from the open set and expanded
+    pub nodes_expanded: usize,
+    // The most cells the open set held at once, counting stale copies waiting to be skipped
+    pub max_frontier_size: usize,
+    // How many cells are on the path found, or 0 if there wasn't one
+    pub path_length: usize,
+    pub elapsed: Duration,
+}
+
+// A snapshot of a search just after it expands a cell, so callers can watch the search run (to animate it, say)
+// without the solver knowing anything about how it's shown
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchStep {
+    // The cell that was just expanded
+    pub expanded: Coordinate,
+    // Every cell waiting in the open set, including stale copies of cells since reached more cheaply
+    pub frontier: Vec<Coordinate>,
+}
+
+// How much work a search may do before it gives up; either limit can be left off
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchLimits {
+    // The most cells the search may expand
+    pub max_nodes: Option<usize>,
+    // The longest the search may run for
+    pub timeout: Option<Duration>,
+}
+impl SearchLimits {
+    // Called just before the search expands another cell, to give up if doing so would go past a limit
+    pub(crate) fn check(&self, stats: &SearchStats, start_time: Instant) -> Result<(), SearchError> {
+        let mut stats = *stats;
+        stats.elapsed = start_time.elapsed();
+        if self.max_nodes.is_some_and(|max_nodes| stats.nodes_expanded >= max_nodes) {
+            return Err(SearchError::NodeLimitExceeded(stats));
+        }
+        if self.timeout.is_some_and(|timeout| stats.elapsed >= timeout) {
+            return Err(SearchError::TimedOut(stats));
+        }
+        Ok(())
+    }
+}
+
+// Why a search couldn't say whether there's a path: it gave up part way (with statistics about how far it got),
+// it was asked to search between cells that can't be on a path, or the route it found couldn't be followed back
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SearchError {
+    NodeLimitExceeded(SearchStats),
+    TimedOut(SearchStats),
+    // solve_between was asked to start or finish somewhere it can't
+    OutOfBounds(Coordinate),
+    InWall(Coordinate),
+    // Following the parents back from the end of the path stopped at this cell rather than at the start, or went round
+    // in a loop through it; the grid's search state has been corrupted somehow, and whatever path came out would be wrong
+    ReconstructionFailed(Coordinate),
+}
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SearchError::NodeLimitExceeded(stats) => write!(f, "search gave up after expanding {} nodes", stats.nodes_expanded),
+            SearchError::TimedOut(stats) => write!(f, "search timed out after {:?}, having expanded {} nodes", stats.elapsed, stats.nodes_expanded),
+            SearchError::OutOfBounds(coordinate) => write!(f, "{} is outside the maze", coordinate),
+            SearchError::InWall(coordinate) => write!(f, "{} is a wall", coordinate),
+            SearchError::ReconstructionFailed(coordinate) => write!(f, "following the path back from its end went wrong at {}", coordinate),
+        }
+    }
+}
+impl std::error::Error for SearchError {}
+
+// Which cells count as next to each other: just the four orthogonal ones, or the diagonals as well
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+impl Connectivity {
+    // What every step cost is multiplied by
+    // With diagonals allowed, costs are scaled by 10 so a diagonal step can cost roughly sqrt(2) times a straight one
+    fn scale(&self) -> usize {
+        match self {
+            Connectivity::Four => 1,
+            Connectivity::Eight => 10,
+        }
+    }
+
+    // The cost of a single step across the maze (left or right), using the grid's cost_x
+    pub(crate) fn horizontal_cost(&self, grid: &Grid<Cell>) -> usize {
+        self.scale() * grid.cost_x
+    }
+
+    // The cost of a single step up or down the maze, using the grid's cost_y
+    pub(crate) fn vertical_cost(&self, grid: &Grid<Cell>) -> usize {
+        self.scale() * grid.cost_y
+    }
+
+    // The cost of a single diagonal step: the length of the diagonal of a cost_x by cost_y cell, rounded to a whole cost
+    // It's never less than either straight step, nor more than the two of them together
+    pub(crate) fn diagonal_cost(&self, grid: &Grid<Cell>) -> usize {
+        (self.scale() as f64 * ((grid.cost_x * grid.cost_x + grid.cost_y * grid.cost_y) as f64).sqrt()).round() as usize
+    }
+
+    // The cost of moving from one cell to a neighbour of it, before the weight of the cell being entered is applied
+    // Stepping through a portal costs the same as the cheaper straight step, however far apart its two ends are
+    pub(crate) fn step_cost_between(&self, grid: &Grid<Cell>, from: Coordinate, to: Coordinate) -> usize {
+        match grid.axis_distances(from, to) {
+            (1, 0) => self.horizontal_cost(grid),
+            (0, 1) => self.vertical_cost(grid),
+            (1, 1) => self.diagonal_cost(grid),
+            _ => self.horizontal_cost(grid).min(self.vertical_cost(grid)),
+        }
+    }
+}
+
+// How the informed searches estimate the remaining cost from a cell to the exit
+// For A* to return shortest paths the estimate must never be more than the real cost (it must be admissible)
+// Every cell weighs at least 1, so estimating as if every step were as cheap as possible keeps these admissible on weighted mazes too
+// Horizontal and vertical distances are costed separately, so they stay admissible when the grid's cost_x and cost_y differ
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Heuristic {
+    // Sum of the horizontal and vertical distances; exact for four-way movement on an open grid, but it overestimates with diagonals
+    Manhattan,
+    // Straight-line distance
+    Euclidean,
+    // The larger of the horizontal and vertical distances, as if diagonal steps cost the same as straight ones
+    Chebyshev,
+    // As many diagonal steps as possible, then straight the rest of the way; exact for eight-way movement on an open grid
+    Octile,
+    // No estimate at all, which turns A* into Dijkstra
+    Zero,
+}
+impl Heuristic {
+    // The heuristic that fits each kind of movement best while staying admissible
+    pub fn default_for(connectivity: Connectivity) -> Heuristic {
+        match connectivity {
+            Connectivity::Four => Heuristic::Manhattan,
+            Connectivity::Eight => Heuristic::Octile,
+        }
+    }
+
+    // The estimated cost from one coordinate to another on grid, in the same units as connectivity's step costs
+    pub(crate) fn estimate(&self, grid: &Grid<Cell>, from: Coordinate, to: Coordinate, connectivity: Connectivity) -> usize {
+        // On a maze that wraps round, the shorter way round is as far as the exit can be
+        let (dx, dy) = grid.axis_distances(from, to);
+        let horizontal_cost = connectivity.horizontal_cost(grid);
+        let vertical_cost = connectivity.vertical_cost(grid);
+        let diagonal_cost = connectivity.diagonal_cost(grid);
+        match self {
+            Heuristic::Manhattan => horizontal_cost * dx + vertical_cost * dy,
+            // Euclidean distance has to be scaled into integer step costs carefully: with eight-way movement a diagonal step
+            // covers sqrt(2) of distance for 14, which is less than 10 per unit of distance, so scaling by 10 would overestimate.
+            // Measure the distance in cost_x by cost_y cells, scale by the cheapest cost per unit of that distance,
+            // and round down so we never round up past the real cost
+            Heuristic::Euclidean => {
+                let (cost_x, cost_y) = (grid.cost_x as f64, grid.cost_y as f64);
+                let cost_per_unit = match connectivity {
+                    Connectivity::Four => connectivity.scale() as f64,
+                    Connectivity::Eight => (connectivity.scale() as f64).min(diagonal_cost as f64 / (cost_x * cost_x + cost_y * cost_y).sqrt()),
+                };
+                let (distance_x, distance_y) = (cost_x * dx as f64, cost_y * dy as f64);
+                (cost_per_unit * (distance_x * distance_x + distance_y * distance_y).sqrt()).floor() as usize
+            },
+            // Every step that moves across costs at least a horizontal step, and every one that moves up or down at least a vertical one
+            Heuristic::Chebyshev => (horizontal_cost * dx).max(vertical_cost * dy),
+            Heuristic::Octile => {
+                let diagonals = dx.min(dy);
+                diagonal_cost * diagonals + horizontal_cost * (dx - diagonals) + vertical_cost * (dy - diagonals)
+            },
+            Heuristic::Zero => 0,
+        }
+    }
+
+    // The estimated cost from a coordinate to the nearest of targets, allowing for the grid's portals
+    // A portal can carry us across the maze in one step, so estimating straight to the target could overestimate.
+    // Any route through portals has to get to a portal, step through it, and then get from a portal to a target,
+    // so the cheapest estimate for those three parts is a lower bound on every route that uses them
+    pub fn estimate_nearest(&self, grid: &Grid<Cell>, from: Coordinate, targets: &[Coordinate], connectivity: Connectivity) -> usize {
+        let direct = |from: Coordinate| targets.iter().map(|&target| self.estimate(grid, from, target, connectivity)).min().unwrap_or(0);
+        let straight_there = direct(from);
+        let (Some(to_portal), Some(from_portal)) = (
+            grid.portals.keys().map(|&portal| self.estimate(grid, from, portal, connectivity)).min(),
+            grid.portals.keys().map(|&portal| direct(portal)).min(),
+        ) else {
+            return straight_there;
+        };
+        straight_there.min(to_portal + connectivity.horizontal_cost(grid).min(connectivity.vertical_cost(grid)) + from_portal)
+    }
+}
+impl FromStr for Heuristic {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "manhattan" => Ok(Heuristic::Manhattan),
+            "euclidean" => Ok(Heuristic::Euclidean),
+            "chebyshev" => Ok(Heuristic::Chebyshev),
+            "octile" => Ok(Heuristic::Octile),
+            "zero" => Ok(Heuristic::Zero),
+            _ => Err(format!("unknown heuristic '{}' (expected manhattan, euclidean, chebyshev, octile or zero)", name)),
+        }
+    }
+}
+impl FromStr for Connectivity {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "4" => Ok(Connectivity::Four),
+            "8" => Ok(Connectivity::Eight),
+            _ => Err(format!("unknown connectivity '{}' (expected 4 or 8)", name)),
+        }
+    }
+}
+
+// The open set for each algorithm: a priority queue for the ones that order on cost/heuristic,
+// a FIFO queue for BFS and a LIFO stack for DFS
+enum Frontier {
+    Heap(BinaryHeap<Cell>),
+    Queue(VecDeque<Cell>),
+    Stack(Vec<Cell>),
+}
+impl Frontier {
+    fn new(algorithm: Algorithm) -> Frontier {
+        match algorithm {
+            Algorithm::AStar | Algorithm::WeightedAStar(_) | Algorithm::Dijkstra | Algorithm::Greedy | Algorithm::Jps | Algorithm::IdaStar => Frontier::Heap(BinaryHeap::new()),
+            Algorithm::Bfs => Frontier::Queue(VecDeque::new()),
+            Algorithm::Dfs => Frontier::Stack(Vec::new()),
+        }
+    }
+
+    fn push(&mut self, cell: Cell) {
+        match self {
+            Frontier::Heap(heap) => heap.push(cell),
+            Frontier::Queue(queue) => queue.push_back(cell),
+            Frontier::Stack(stack) => stack.push(cell),
+        }
+    }
+
+    fn pop(&mut self) -> Option<Cell> {
+        match self {
+            Frontier::Heap(heap) => heap.pop(),
+            Frontier::Queue(queue) => queue.pop_front(),
+            Frontier::Stack(stack) => stack.pop(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Frontier::Heap(heap) => heap.len(),
+            Frontier::Queue(queue) => queue.len(),
+            Frontier::Stack(stack) => stack.len(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Frontier::Heap(heap) => heap.is_empty(),
+            Frontier::Queue(queue) => queue.is_empty(),
+            Frontier::Stack(stack) => stack.is_empty(),
+        }
+    }
+
+    // The coordinates of every cell waiting in the open set, in no particular order
+    fn coordinates(&self) -> Vec<Coordinate> {
+        match self {
+            Frontier::Heap(heap) => heap.iter().map(|cell| cell.coordinate).collect(),
+            Frontier::Queue(queue) => queue.iter().map(|cell| cell.coordinate).collect(),
+            Frontier::Stack(stack) => stack.iter().map(|cell| cell.coordinate).collect(),
+        }
+    }
+
+    // Only the priority-ordered frontiers can pop a cell before its cheapest route has been found,
+    // so only they need to revisit cells; BFS and DFS take the first route they find to each cell
+    fn revisits(&self) -> bool {
+        matches!(self, Frontier::Heap(_))
+    }
+}
+
+// Run A* over the grid from its entrance to its nearest exit, returning the path between them (entrance first),
+// or Unreachable if no exit can be reached from the entrance
+pub fn solve(grid: &mut Grid<Cell>) -> SolveOutcome {
+    solve_with(grid, Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, None).into()
+}
+
+// Breadth-first search from the entrance to the exit
+// The open set is a VecDeque, so cells come out in the order they were found; on an unweighted maze this
+// means the first route BFS finds to the exit is a shortest one, the same length as A* finds
+pub fn solve_bfs(grid: &mut Grid<Cell>) -> (Option<Solution>, SearchStats) {
+    solve_with(grid, Algorithm::Bfs, Connectivity::Four, Heuristic::Manhattan, None)
+}
+
+// Depth-first search from the entrance to the exit
+// The open set is a plain Vec used as a stack, so this dives down one corridor at a time and returns the first route it
+// finds, which is a valid path but usually not the shortest one. Cells are never revisited once reached,
+// so it still terminates on mazes with loops
+pub fn solve_dfs(grid: &mut Grid<Cell>) -> (Option<Solution>, SearchStats) {
+    solve_with(grid, Algorithm::Dfs, Connectivity::Four, Heuristic::Manhattan, None)
+}
+
+// Run the given search algorithm over the grid from its entrance to any of its exits, moving between cells as connectivity allows
+// and estimating the distance left with heuristic (which BFS, DFS and Dijkstra ignore)
+// The search stops at the first exit it pops, which for A* and Dijkstra is the cheapest one to reach
+// Returns None if no exit can be reached from the entrance, along with statistics about the search either way
+// If on_step is given, it's called with a SearchStep after every cell the search expands
+pub fn solve_with(grid: &mut Grid<Cell>, algorithm: Algorithm, connectivity: Connectivity, heuristic: Heuristic,
+                  on_step: Option<&mut dyn FnMut(&SearchStep)>) -> (Option<Solution>, SearchStats) {
+    solve_with_limits(grid, algorithm, connectivity, heuristic, SearchLimits::default(), on_step)
+        .expect("a search without limits can't hit one, and its parents always lead back to the start")
+}
+
+// solve_with, but giving up with a SearchError once the search goes past any of limits
+// For embedding the solver somewhere a huge maze mustn't hold everything up; the partial search is dropped when it gives up
+pub fn solve_with_limits(grid: &mut Grid<Cell>, algorithm: Algorithm, connectivity: Connectivity, heuristic: Heuristic,
+                         limits: SearchLimits, on_step: Option<&mut dyn FnMut(&SearchStep)>)
+                         -> Result<(Option<Solution>, SearchStats), SearchError> {
+    let (start, goals) = (grid.entrance_location, grid.exits.clone());
+    search(grid, start, &goals, &HashSet::new(), algorithm, connectivity, heuristic, limits, on_step)
+}
+
+// solve_with_limits, but returning only what the path to the exit costs, without ever building the path itself
+// stats.path_length is still filled in, by counting back along the parents rather than collecting them into a Vec
+// Worth it on very large mazes when only the numbers are wanted (for benchmarking memory use, say); on everyday mazes
+// the path is small next to the search's own bookkeeping. Jump Point Search builds its path as it backtracks through
+// the jump points, and IDA* holds its path all along, so with Algorithm::Jps or Algorithm::IdaStar this saves nothing
+pub fn solve_cost_with_limits(grid: &mut Grid<Cell>, algorithm: Algorithm, connectivity: Connectivity, heuristic: Heuristic,
+                              limits: SearchLimits, on_step: Option<&mut dyn FnMut(&SearchStep)>)
+                              -> Result<(Option<usize>, SearchStats), SearchError> {
+    let (start, goals) = (grid.entrance_location, grid.exits.clone());
+    let algorithm = match algorithm {
+        Algorithm::Jps if is_uniform_cost(grid) => {
+            let (solution, stats) = solve_jps(grid, start, &goals, connectivity, heuristic, limits, on_step)?;
+            return Ok((solution.map(|solution| solution.cost), stats));
+        },
+        Algorithm::Jps => Algorithm::AStar,
+        Algorithm::IdaStar => {
+            let (solution, stats) = solve_ida_star(grid, start, &goals, &HashSet::new(), connectivity, heuristic, limits, on_step)?;
+            return Ok((solution.map(|solution| solution.cost), stats));
+        },
+        algorithm => algorithm,
+    };
+    let start_time = Instant::now();
+    let (exit_reached, mut stats) = search_to_exit(grid, start, &goals, &HashSet::new(), algorithm, connectivity, heuristic, limits, on_step)?;
+    let cost = match exit_reached {
+        Some(exit) => {
+            stats.path_length = 0;
+            walk_back(grid, start, exit, |_| stats.path_length += 1)?;
+            Some(grid[exit].cost)
+        },
+        None => None,
+    };
+    stats.elapsed = start_time.elapsed();
+    Ok((cost, stats))
+}
+
+// Find a path between any two open cells with A*, rather than from the entrance to an exit
+// Errors if either coordinate is outside the grid or a wall
+pub fn solve_between(grid: &mut Grid<Cell>, start: Coordinate, goal: Coordinate) -> Result<(Option<Solution>, SearchStats), SearchError> {
+    for coordinate in [start, goal] {
+        match grid.try_cell_at(coordinate) {
+            None => return Err(SearchError::OutOfBounds(coordinate)),
+            Some(cell) if cell.cell_type == CellType::Wall => return Err(SearchError::InWall(coordinate)),
+            Some(_) => (),
+        }
+    }
+    search(grid, start, &[goal], &HashSet::new(), Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, SearchLimits::default(), None)
+}
+
+// solve_with_limits, but treating every cell in blocked as a wall for this search only, for mazes where open cells come and go
+// (doors, or other things in the way) without the grid itself being changed. Blocked exits can't be reached, and neither can
+// anything a diagonal step would have to squeeze past a blocked cell to get to; the entrance is still where the search starts
+// from even if it's blocked. Jump Point Search only knows about the grid's own walls, so with anything blocked it runs A* instead
+pub fn solve_avoiding(grid: &mut Grid<Cell>, blocked: &HashSet<Coordinate>, algorithm: Algorithm, connectivity: Connectivity,
+                      heuristic: Heuristic, limits: SearchLimits) -> Result<(Option<Solution>, SearchStats), SearchError> {
+    let (start, goals) = (grid.entrance_location, grid.exits.clone());
+    search(grid, start, &goals, blocked, algorithm, connectivity, heuristic, limits, None)
+}
+
+// solve_with_limits, but when no exit can be reached, returning the path to whichever reachable cell the heuristic puts closest
+// to an exit instead of nothing, so something that has to move (a character in a game, say) can at least head that way
+// Solution::exit is the cell the path ends at, which is only an exit if one could be reached; ties between cells equally
+// close go to the cheaper one to get to. With no exit reachable the whole of the entrance's part of the maze gets searched.
+// Jump Point Search and IDA* don't keep a route to every cell they reach, so A* is used in their place
+pub fn solve_best_effort(grid: &mut Grid<Cell>, algorithm: Algorithm, connectivity: Connectivity, heuristic: Heuristic, limits: SearchLimits)
+                         -> Result<(Solution, SearchStats), SearchError> {
+    let (start, goals) = (grid.entrance_location, grid.exits.clone());
+    let algorithm = match algorithm {
+        Algorithm::Jps | Algorithm::IdaStar => Algorithm::AStar,
+        algorithm => algorithm,
+    };
+    let start_time = Instant::now();
+    let (exit_reached, mut stats) = search_to_exit(grid, start, &goals, &HashSet::new(), algorithm, connectivity, heuristic, limits, None)?;
+    let end = exit_reached.unwrap_or_else(|| closest_reached(grid, start, &goals, connectivity, heuristic));
+    let path = path_back_from(grid, start, end)?;
+    stats.path_length = path.len();
+    stats.elapsed = start_time.elapsed();
+    Ok((Solution { path, exit: end, cost: grid[end].cost }, stats))
+}
+
+// solve_with, but stopping once the search has expanded max_visited cells and returning SolveOutcome::Partial, with the path to
+// whichever cell reached so far the heuristic puts closest to an exit (ties going to the cheaper one to get to), rather than
+// failing as SearchLimits::max_nodes does. Counting cells rather than time means the same maze stops in the same place on
+// any machine. The reason is SearchError::NodeLimitExceeded. Jump Point Search and IDA* don't keep a route to every cell
+// they reach, so A* is used in their place. The only error is ReconstructionFailed, if the search's parents don't lead back
+pub fn solve_with_max_visited(grid: &mut Grid<Cell>, algorithm: Algorithm, connectivity: Connectivity, heuristic: Heuristic,
+                              max_visited: usize) -> Result<SolveOutcome, SearchError> {
+    let (start, goals) = (grid.entrance_location, grid.exits.clone());
+    let algorithm = match algorithm {
+        Algorithm::Jps | Algorithm::IdaStar => Algorithm::AStar,
+        algorithm => algorithm,
+    };
+    let limits = SearchLimits { max_nodes: Some(max_visited), ..SearchLimits::default() };
+    match search_to_exit(grid, start, &goals, &HashSet::new(), algorithm, connectivity, heuristic, limits, None) {
+        Ok((Some(exit), mut stats)) => {
+            let path = path_back_from(grid, start, exit)?;
+            stats.path_length = path.len();
+            Ok(SolveOutcome::Found { path, exit, cost: grid[exit].cost, stats })
+        },
+        Ok((None, stats)) => Ok(SolveOutcome::Unreachable { stats }),
+        Err(reason @ SearchError::NodeLimitExceeded(mut stats)) => {
+            let end = closest_reached(grid, start, &goals, connectivity, heuristic);
+            let path = path_back_from(grid, start, end)?;
+            stats.path_length = path.len();
+            Ok(SolveOutcome::Partial { path, cost: grid[end].cost, reason, stats })
+        },
+        Err(error) => Err(error),
+    }
+}
+
+// The cell the last search reached that the heuristic puts closest to any of goals, breaking ties by cost and then row and column
+// Every cell the search reached has a parent, apart from start
+fn closest_reached(grid: &Grid<Cell>, start: Coordinate, goals: &[Coordinate], connectivity: Connectivity, heuristic: Heuristic) -> Coordinate {
+    grid.cells.iter()
+        .filter(|cell| cell.parent_coord.is_some() || cell.coordinate == start)
+        .min_by_key(|cell| (heuristic.estimate_nearest(grid, cell.coordinate, goals, connectivity), cell.cost, cell.coordinate.y, cell.coordinate.x))
+        .map_or(start, |cell| cell.coordinate)
+}
+
+// The search behind all the solve functions: from start to whichever of goals it reaches first, never stepping into blocked
+// Solution::exit is the goal reached
+#[allow(clippy::too_many_arguments)]
+fn search(grid: &mut Grid<Cell>, start: Coordinate, goals: &[Coordinate], blocked: &HashSet<Coordinate>, algorithm: Algorithm, connectivity: Connectivity, heuristic: Heuristic,
+          limits: SearchLimits, on_step: Option<&mut dyn FnMut(&SearchStep)>) -> Result<(Option<Solution>, SearchStats), SearchError> {
+    // Jump Point Search has its own loop; it only finds shortest paths when every cell costs the same and there are no portals,
+    // so on other mazes run plain A* instead
+    let algorithm = match algorithm {
+        Algorithm::Jps if is_uniform_cost(grid) && blocked.is_empty() => return solve_jps(grid, start, goals, connectivity, heuristic, limits, on_step),
+        Algorithm::Jps => Algorithm::AStar,
+        Algorithm::IdaStar => return solve_ida_star(grid, start, goals, blocked, connectivity, heuristic, limits, on_step),
+        algorithm => algorithm,
+    };
+    let start_time = Instant::now();
+    let (exit_reached, mut stats) = search_to_exit(grid, start, goals, blocked, algorithm, connectivity, heuristic, limits, on_step)?;
+    // If we never reached an exit there's no route to follow back
+    let Some(exit) = exit_reached else {
+        stats.elapsed = start_time.elapsed();
+        return Ok((None, stats));
+    };
+    let path = path_back_from(grid, start, exit)?;
+    stats.path_length = path.len();
+    stats.elapsed = start_time.elapsed();
+    Ok((Some(Solution { path, exit, cost: grid[exit].cost }), stats))
+}
+
+// The route the last search took from start to end, start first, followed back through the grid's parent_coords
+fn path_back_from(grid: &Grid<Cell>, start: Coordinate, end: Coordinate) -> Result<Vec<Coordinate>, SearchError> {
+    let mut path = Vec::new();
+    walk_back(grid, start, end, |coordinate| path.push(coordinate))?;
+    path.reverse();
+    Ok(path)
+}
+
+// Visit every cell from end back to start, end first, by following parent_coords until a cell without a parent
+// That cell has to be start: if it isn't, or the parents go round in a loop (no path can have more cells than the grid),
+// the search state is corrupt, and it's a ReconstructionFailed error rather than a wrong path or a walk that never ends
+fn walk_back(grid: &Grid<Cell>, start: Coordinate, end: Coordinate, mut visit: impl FnMut(Coordinate)) -> Result<(), SearchError> {
+    let mut current = end;
+    visit(current);
+    for _ in 1..grid.cells.len() {
+        match grid[current].parent_coord {
+            Some(parent) => current = parent,
+            None if current == start => return Ok(()),
+            None => return Err(SearchError::ReconstructionFailed(current)),
+        }
+        visit(current);
+    }
+    if current == start && grid[current].parent_coord.is_none() {
+        Ok(())
+    }
+    else {
+        Err(SearchError::ReconstructionFailed(current))
+    }
+}
+
+// Run the search loop until it pops one of goals, returning the goal reached (if any) with the search's statistics
+// The route there is left in the grid's parent_coords, for the caller to follow back if it wants it; stats.path_length
+// and stats.elapsed are left for the caller to fill in too
+#[allow(clippy::too_many_arguments)]
+fn search_to_exit(grid: &mut Grid<Cell>, start: Coordinate, goals: &[Coordinate], blocked: &HashSet<Coordinate>, algorithm: Algorithm, connectivity: Connectivity, heuristic: Heuristic,
+                  limits: SearchLimits, mut on_step: Option<&mut dyn FnMut(&SearchStep)>) -> Result<(Option<Coordinate>, SearchStats), SearchError> {
+    let start_time = Instant::now();
+    let mut stats = SearchStats::default();
+    // Declare all our collections to store our working data
+    let mut open_set = Frontier::new(algorithm);
+    // The best cost (g-score) found so far for every coordinate we've reached
+    // Looking costs up here is O(1), where scanning the heap to see if a cell is already queued was O(n) per neighbour
+    let mut best_costs = HashMap::new();
+    // Clear out anything an earlier search on this grid left in the cells
+    grid.reset();
+    let mut current_cell = grid[start];
+    best_costs.insert(current_cell.coordinate, current_cell.cost);
+
+    // Track which exit we pop first, if any; if the open set runs dry first, every exit is walled off
+    let mut exit_reached = None;
+
+    //println!("current_cell: {:?} ", current_cell);
+    open_set.push(current_cell);
+    while !open_set.is_empty() {
+        // Get the next item from the open set
+        // For the heap-based algorithms this is always the lowest priority item, due to our custom definition of Ord on the Cells in open_set
+        current_cell = open_set.pop().unwrap();
+        // A cell gets pushed again whenever we find a cheaper route to it, leaving the older copy behind in the heap
+        // If the popped copy costs more than the best we've recorded, it's one of those stale copies, so skip it
+        if current_cell.cost > best_costs[&current_cell.coordinate] {
+            continue;
+        }
+        if goals.contains(&current_cell.coordinate) {
+            // If the popped cell is an exit, we're done, so break the loop
+            exit_reached = Some(current_cell.coordinate);
+            break;
+        }
+        // If the popped cell is not an exit, expand it by getting its neighbours
+        limits.check(&stats, start_time)?;
+        stats.nodes_expanded += 1;
+        let neighbours = grid.neighbours(current_cell.coordinate, connectivity);
+
+        // Loop across the neighbours we just found
+        for neighbour in neighbours {
+            // Neighbours we've already expanded aren't skipped outright: with a heuristic that isn't consistent (Manhattan with
+            // diagonal moves, say) we can expand a cell before finding the cheapest route to it. The best cost check below
+            // reopens the cell if this route is cheaper, and rejects it just as quickly if it isn't, so no closed set is needed
+            //print!("neighbour.x: {}, neighbour.y: {}, width: {}, height: {} \n", neighbour.x, neighbour.y, grid.width, grid.height);
+
+            // With several exits the estimate is to whichever exit is closest (portals included), which keeps it an underestimate
+            // It needs the whole grid, so work it out (and the cost of the step there) before we borrow the neighbour cell from the grid mutably
+            let h = heuristic.estimate_nearest(grid, neighbour, goals, connectivity);
+            let step_cost = connectivity.step_cost_between(grid, current_cell.coordinate, neighbour);
+            let step_blocked = grid.step_blocked(current_cell.coordinate, neighbour, blocked);
+            // Get the neighbour cell itself from the grid using its coordinates
+            let neighbour_cell = grid.cell_at_mut(neighbour);
+            if neighbour_cell.cell_type == CellType::Wall || step_blocked || neighbour == start {
+                // If the neighbour is a wall (or blocked for this search) or where we started, we can safely skip it
+                // (walls are irrelevant, and there's no cheaper way to the start than starting there)
+                // Any other entrance is an open cell like the rest, since solve_between might start somewhere else or be heading there
+                //print!("skipping wall or start: {:?} at {:?} \n", neighbour_cell.cell_type, neighbour_cell.coordinate);
+                continue;
+            }
+
+            // A neighbour cell's cost is the cost of the current cell plus the cost of the step to it, scaled by the neighbour's weight
+            // Note that this is the g-score only; the heuristic is added separately into f_score below
+            let tentative_cost = current_cell.cost + step_cost * neighbour_cell.weight;
+            // If we've never reached the neighbour before, or if the tentative cost is less than its best known cost, update the neighbour cell
+            // We update on the basis of the tentative cost being less than the neighbour cell's cost because we want to find the shortest path, 
+            // and a neighbour may have already been found in another exploration of the maze, but with a higher cost
+            // We only ever care about the lower cost; if we found a path to a cell with a lower cost, great!
+            // BFS and DFS never revisit a cell once they've found a route to it
+            if best_costs.get(&neighbour).is_none_or(|&best_cost| open_set.revisits() && tentative_cost < best_cost) {
+                best_costs.insert(neighbour, tentative_cost);
+                neighbour_cell.parent_coord = Some(current_cell.coordinate);
+                neighbour_cell.cost = tentative_cost;
+                neighbour_cell.h = h;
+                // The f-score is what the open set sorts on; for A* it's the true cost so far plus the estimated cost to the exit
+                neighbour_cell.f_score = algorithm.priority(neighbour_cell.cost, neighbour_cell.h);
+                // Now that we've updated the neighbour, add it to the open set. We don't try to find and remove any older, dearer copy
+                // of it already in there (a heap can't do that cheaply); instead it gets skipped as stale when it's popped
+                open_set.push(*neighbour_cell);
+            }
+        }
+        stats.max_frontier_size = stats.max_frontier_size.max(open_set.len());
+        if let Some(on_step) = on_step.as_mut() {
+            on_step(&SearchStep { expanded: current_cell.coordinate, frontier: open_set.coordinates() });
+        }
+    }
+    Ok((exit_reached, stats))
+}
+
+// Turn a path through grid into compass directions, one per step: "N" is up the maze (towards row 0), "E" is right, and so on
+// A jump through one of the grid's portals is "P", and so is a step round the edge of a maze that wraps, since neither is
+// next door on the page
+// Panics if two consecutive coordinates aren't one of those steps (diagonally adjacent, say, since diagonal moves have no
+// compass direction here, or further apart with no portal between them), as the path can't have come from a search of grid
+pub fn path_to_directions(grid: &Grid<Cell>, path: &[Coordinate]) -> Vec<&'static str> {
+    path.windows(2).map(|step| {
+        let (from, to) = (step[0], step[1]);
+        if to.x == from.x && to.y + 1 == from.y {
+            "N"
+        }
+        else if to.x == from.x + 1 && to.y == from.y {
+            "E"
+        }
+        else if to.x == from.x && to.y == from.y + 1 {
+            "S"
+        }
+        else if to.x + 1 == from.x && to.y == from.y {
+            "W"
+        }
+        // Anything else that's one step away on a maze that wraps has gone round the edge
+        else if grid.portals.get(&from) == Some(&to) || (grid.toroidal && matches!(grid.axis_distances(from, to), (0, 1) | (1, 0))) {
+            "P"
+        }
+        else {
+            panic!("path steps from {} to {}, which aren't adjacent", from, to);
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grid::MAX_SIDE, load_maze};
+
+    fn coordinates(pairs: &[(u32, u32)]) -> Vec<Coordinate> {
+        pairs.iter().map(|&(x, y)| Coordinate { x, y }).collect()
+    }
+
+    #[test]
+    fn astar_finds_the_shortest_path_through_vlarge() {
+        let SolveOutcome::Found { path, cost, .. } = solve(&mut load_maze("maze-VLarge")) else {
+            panic!("maze-VLarge has no solution");
+        };
+        assert_eq!((path.len(), cost), (3691, 3690));
+    }
+
+    // The heuristic steers A* towards the exit, so it has to look at fewer cells than Dijkstra to find a path as short
+    #[test]
+    fn astar_expands_fewer_cells_than_dijkstra() {
+        let grid = load_maze("maze-VLarge");
+        let (astar, astar_stats) = solve_with(&mut grid.clone(), Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, None);
+        let (dijkstra, dijkstra_stats) = solve_with(&mut grid.clone(), Algorithm::Dijkstra, Connectivity::Four, Heuristic::Manhattan, None);
+        assert_eq!(astar.unwrap().cost, dijkstra.unwrap().cost);
+        assert!(astar_stats.nodes_expanded < dijkstra_stats.nodes_expanded,
+                "A* expanded {} cells and Dijkstra {}", astar_stats.nodes_expanded, dijkstra_stats.nodes_expanded);
+    }
+
+    #[test]
+    fn directions_jump_through_a_portal_pair() {
+        let mut grid: Grid<Cell> = "#######\n-A#A---\n#######".parse().unwrap();
+        let SolveOutcome::Found { path, .. } = solve(&mut grid) else {
+            panic!("no path through the portal");
+        };
+        assert_eq!(path, coordinates(&[(0, 1), (1, 1), (3, 1), (4, 1), (5, 1), (6, 1)]));
+        assert_eq!(path_to_directions(&grid, &path), ["E", "P", "E", "E", "E"]);
+    }
+
+    #[test]
+    fn directions_step_round_the_edge_of_a_wrapping_maze() {
+        let mut grid: Grid<Cell> = "#-#\n#-#\n#-#".parse().unwrap();
+        grid.set_toroidal(true);
+        assert_eq!(path_to_directions(&grid, &coordinates(&[(1, 0), (1, 2), (1, 1)])), ["P", "N"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "aren't adjacent")]
+    fn directions_reject_a_jump_with_no_portal() {
+        let grid: Grid<Cell> = "#######\n-A#A---\n#######".parse().unwrap();
+        path_to_directions(&grid, &coordinates(&[(0, 1), (5, 1)]));
+    }
+
+    #[test]
+    #[should_panic(expected = "aren't adjacent")]
+    fn directions_reject_a_wrap_on_a_maze_that_doesnt() {
+        let grid: Grid<Cell> = "#-#\n#-#\n#-#".parse().unwrap();
+        path_to_directions(&grid, &coordinates(&[(1, 0), (1, 2)]));
+    }
+
+    #[test]
+    fn solve_finds_the_path_through_a_tiny_maze() {
+        let SolveOutcome::Found { path, exit, cost, .. } = solve(&mut "#-#\n#-#\n#-#".parse().unwrap()) else {
+            panic!("no path straight down");
+        };
+        assert_eq!(path, coordinates(&[(1, 0), (1, 1), (1, 2)]));
+        assert_eq!((exit, cost), (Coordinate { x: 1, y: 2 }, 2));
+    }
+
+    // The exit is boxed in by walls, so the open set runs dry without ever reaching it
+    #[test]
+    fn a_sealed_exit_is_unreachable() {
+        let mut grid: Grid<Cell> = "#####\n----#\n#-###\n###--\n#####".parse().unwrap();
+        assert!(matches!(solve(&mut grid), SolveOutcome::Unreachable { .. }));
+        for algorithm in [Algorithm::Bfs, Algorithm::Dfs, Algorithm::Dijkstra, Algorithm::Jps, Algorithm::IdaStar] {
+            let (solution, _) = solve_with(&mut grid, algorithm, Connectivity::Four, Heuristic::Manhattan, None);
+            assert_eq!(solution, None, "{:?}", algorithm);
+        }
+    }
+
+    #[test]
+    fn bfs_paths_are_as_short_as_astar() {
+        for name in ["maze-Easy", "maze-Medium", "maze-Large"] {
+            let grid = load_maze(name);
+            let (bfs, _) = solve_bfs(&mut grid.clone());
+            let SolveOutcome::Found { path, .. } = solve(&mut grid.clone()) else {
+                panic!("{} has no solution", name);
+            };
+            assert_eq!(bfs.unwrap().path.len(), path.len(), "{}", name);
+        }
+    }
+
+    // A room full of loops, where DFS wanders about before it gets to the exit
+    #[test]
+    fn dfs_returns_a_connected_path_through_a_maze_with_loops() {
+        let mut grid: Grid<Cell> = "#######\n------#\n#-#-#-#\n#-----#\n#-#-#-#\n#------\n#######".parse().unwrap();
+        let (solution, _) = solve_dfs(&mut grid);
+        let path = solution.unwrap().path;
+        assert_eq!((path.first(), path.last()), (Some(&grid.entrance()), Some(&grid.exit())));
+        for step in path.windows(2) {
+            assert_eq!(step[0].x.abs_diff(step[1].x) + step[0].y.abs_diff(step[1].y), 1, "{} to {} isn't one step", step[0], step[1]);
+        }
+        assert!(path.iter().all(|&coordinate| grid[coordinate].cell_type != CellType::Wall));
+        assert_eq!(path.iter().collect::<HashSet<_>>().len(), path.len(), "DFS went round a loop");
+        // Never shorter than the shortest path
+        assert!(path.len() >= 11);
+    }
+
+    #[test]
+    fn directions_follow_a_zig_zag() {
+        let grid: Grid<Cell> = "#####\n-----\n#####\n#####".parse().unwrap();
+        let path = coordinates(&[(0, 1), (1, 1), (1, 2), (2, 2), (2, 1), (2, 0), (1, 0), (1, 1)]);
+        assert_eq!(path_to_directions(&grid, &path), ["E", "S", "E", "N", "N", "W", "S"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "aren't adjacent")]
+    fn directions_reject_a_diagonal_step() {
+        let grid: Grid<Cell> = "#####\n-----\n#####".parse().unwrap();
+        path_to_directions(&grid, &coordinates(&[(0, 1), (1, 2)]));
+    }
+
+    #[test]
+    fn diagonals_shorten_the_path_across_an_open_room() {
+        let grid: Grid<Cell> = "#######\n------#\n#-----#\n#-----#\n#-----#\n#------\n#######".parse().unwrap();
+        let (four, _) = solve_with(&mut grid.clone(), Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, None);
+        let (eight, _) = solve_with(&mut grid.clone(), Algorithm::AStar, Connectivity::Eight, Heuristic::Octile, None);
+        let (four, eight) = (four.unwrap(), eight.unwrap());
+        // Four ways it's 10 steps; eight ways it's a step in, four diagonals and a step out, with every cost scaled by 10
+        assert_eq!((four.path.len(), four.cost), (11, 10));
+        assert_eq!((eight.path.len(), eight.cost), (7, 10 + 4 * 14 + 10));
+        assert!(eight.cost < four.cost * 10);
+    }
+
+    // Straight across is through mud costing 9 a cell; round the top is two steps longer but costs 1 a step
+    #[test]
+    fn a_longer_but_cheaper_route_beats_a_short_expensive_one() {
+        let mut grid: Grid<Cell> = "#######\n#-----#\n--999--\n#-----#\n#######".parse().unwrap();
+        for algorithm in [Algorithm::AStar, Algorithm::Dijkstra, Algorithm::Jps] {
+            let (solution, _) = solve_with(&mut grid, algorithm, Connectivity::Four, Heuristic::Manhattan, None);
+            let solution = solution.unwrap();
+            assert_eq!((solution.path.len(), solution.cost), (9, 8), "{:?}", algorithm);
+            assert!(solution.path.iter().all(|&coordinate| grid[coordinate].weight == 1), "{:?}", algorithm);
+        }
+    }
+
+    // The mud at (4, 2) is first reached diagonally from (3, 1), costing 110 + 14 * 9, and then more cheaply straight across
+    // from (3, 2), at 114 + 10 * 9; both copies end up in the open set at once, and only the cheaper route is kept
+    #[test]
+    fn a_cell_reached_more_cheaply_later_keeps_the_cheaper_route() {
+        let mut grid: Grid<Cell> = "######\n-9---#\n#---9#\n#-----\n######".parse().unwrap();
+        let mud = Coordinate { x: 4, y: 2 };
+        let (mut most_copies, mut expanded) = (0, Vec::new());
+        let mut on_step = |step: &SearchStep| {
+            most_copies = most_copies.max(step.frontier.iter().filter(|&&coordinate| coordinate == mud).count());
+            expanded.push(step.expanded);
+        };
+        let (solution, stats) = solve_with(&mut grid, Algorithm::Dijkstra, Connectivity::Eight, Heuristic::Octile, Some(&mut on_step));
+        assert_eq!(most_copies, 2);
+        assert_eq!((grid[mud].cost, grid[mud].parent_coord), (204, Some(Coordinate { x: 3, y: 2 })));
+        assert_eq!(expanded.iter().collect::<HashSet<_>>().len(), expanded.len(), "a cell was expanded twice");
+        assert_eq!(stats.nodes_expanded, expanded.len());
+        assert_eq!(solution.unwrap().cost, 138);
+    }
+
+    #[test]
+    fn a_tiny_node_limit_gives_up_on_vlarge() {
+        let mut grid = load_maze("maze-VLarge");
+        let limits = SearchLimits { max_nodes: Some(100), ..SearchLimits::default() };
+        match solve_with_limits(&mut grid, Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, limits, None) {
+            Err(SearchError::NodeLimitExceeded(stats)) => assert_eq!(stats.nodes_expanded, 100),
+            other => panic!("expected the node limit to be hit, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn a_zero_timeout_gives_up_straight_away() {
+        let mut grid = load_maze("maze-VLarge");
+        let limits = SearchLimits { timeout: Some(Duration::ZERO), ..SearchLimits::default() };
+        let result = solve_with_limits(&mut grid, Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, limits, None);
+        assert!(matches!(result, Err(SearchError::TimedOut(stats)) if stats.nodes_expanded == 0), "{:?}", result);
+    }
+
+    #[test]
+    fn limits_the_search_stays_within_change_nothing() {
+        let mut grid = load_maze("maze-Medium");
+        let limits = SearchLimits { max_nodes: Some(grid.width() * grid.height()), timeout: Some(Duration::from_secs(60)) };
+        let (solution, _) = solve_with_limits(&mut grid, Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, limits, None).unwrap();
+        assert_eq!(solution.unwrap().cost, 320);
+    }
+
+    #[test]
+    fn weighted_astar_expands_fewer_cells_within_its_bound() {
+        let grid = load_maze("maze-Large");
+        let (astar, astar_stats) = solve_with(&mut grid.clone(), Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, None);
+        let (weighted, weighted_stats) = solve_with(&mut grid.clone(), Algorithm::WeightedAStar(2.0), Connectivity::Four, Heuristic::Manhattan, None);
+        let (astar, weighted) = (astar.unwrap(), weighted.unwrap());
+        assert!(weighted.cost <= 2 * astar.cost, "weighted A*'s path costs {} and the shortest {}", weighted.cost, astar.cost);
+        assert_eq!(grid.validate_path(&weighted.path, Connectivity::Four), Ok(weighted.cost));
+        assert!(weighted_stats.nodes_expanded * 2 <= astar_stats.nodes_expanded,
+                "weighted A* expanded {} cells and A* {}", weighted_stats.nodes_expanded, astar_stats.nodes_expanded);
+    }
+
+    #[test]
+    fn a_weight_of_one_is_plain_astar() {
+        let grid = load_maze("maze-Medium");
+        let (astar, astar_stats) = solve_with(&mut grid.clone(), Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, None);
+        let (weighted, weighted_stats) = solve_with(&mut grid.clone(), Algorithm::WeightedAStar(1.0), Connectivity::Four, Heuristic::Manhattan, None);
+        assert_eq!(weighted, astar);
+        assert_eq!(weighted_stats.nodes_expanded, astar_stats.nodes_expanded);
+    }
+
+    #[test]
+    fn solve_between_joins_two_cells_inside_the_maze() {
+        let mut grid: Grid<Cell> = "#######\n-------\n#-###-#\n#-#---#\n#######".parse().unwrap();
+        let (start, goal) = (Coordinate { x: 1, y: 3 }, Coordinate { x: 3, y: 3 });
+        let (solution, _) = solve_between(&mut grid, start, goal).unwrap();
+        let solution = solution.unwrap();
+        // Round by the top, as the wall between them is in the way
+        assert_eq!(solution.path, coordinates(&[(1, 3), (1, 2), (1, 1), (2, 1), (3, 1), (4, 1), (5, 1), (5, 2), (5, 3), (4, 3), (3, 3)]));
+        assert_eq!((solution.exit, solution.cost), (goal, 10));
+    }
+
+    #[test]
+    fn solve_between_rejects_walls_and_cells_outside_the_maze() {
+        let mut grid: Grid<Cell> = "#######\n-------\n#######".parse().unwrap();
+        let open = Coordinate { x: 3, y: 1 };
+        assert!(matches!(solve_between(&mut grid, Coordinate { x: 3, y: 0 }, open), Err(SearchError::InWall(_))));
+        assert!(matches!(solve_between(&mut grid, open, Coordinate { x: 7, y: 1 }), Err(SearchError::OutOfBounds(_))));
+    }
+
+    #[test]
+    fn solving_twice_gives_the_same_answer() {
+        let mut grid = load_maze("maze-Medium");
+        let SolveOutcome::Found { path, cost, .. } = solve(&mut grid) else {
+            panic!("maze-Medium has no solution");
+        };
+        assert_eq!(cost, 320);
+        let SolveOutcome::Found { path: second_path, cost: second_cost, .. } = solve(&mut grid) else {
+            panic!("maze-Medium has no solution the second time");
+        };
+        assert_eq!((second_path, second_cost), (path, cost));
+    }
+
+    // Only where the search starts is skipped; a second entrance on the way is as open as any other cell
+    #[test]
+    fn a_second_entrance_can_be_walked_through() {
+        let mut grid: Grid<Cell> = "#-#####\n#-----#\n#####-#\n#-----#\n#-#####".parse().unwrap();
+        let second_entrance = Coordinate { x: 3, y: 1 };
+        grid.cell_at_mut(second_entrance).cell_type = CellType::Entrance;
+        let SolveOutcome::Found { path, cost, .. } = solve(&mut grid) else {
+            panic!("the corridor past the second entrance is blocked");
+        };
+        assert!(path.contains(&second_entrance));
+        assert_eq!(cost, 12);
+        let (solution, _) = solve_between(&mut grid, Coordinate { x: 5, y: 3 }, Coordinate { x: 1, y: 1 }).unwrap();
+        assert!(solution.unwrap().path.contains(&second_entrance));
+    }
+
+    // The entrance is never stepped into, so on an unweighted maze the cost is one less than the number of cells on the path
+    #[test]
+    fn cost_and_length_agree_only_without_weights() {
+        for name in ["maze-Easy", "maze-Medium"] {
+            let mut grid = load_maze(name);
+            let SolveOutcome::Found { path, cost, stats, .. } = solve(&mut grid) else {
+                panic!("{} has no solution", name);
+            };
+            assert_eq!((cost + 1, stats.path_length), (path.len(), path.len()), "{}", name);
+        }
+        let mut grid: Grid<Cell> = "#####\n-5-3-\n#####".parse().unwrap();
+        let SolveOutcome::Found { path, cost, .. } = solve(&mut grid) else {
+            panic!("the weighted corridor has no solution");
+        };
+        assert_eq!((path.len(), cost), (5, 10));
+    }
+
+    // Deep and narrow under the wall between S and G, or shallow and wide over it: which is cheaper depends on what
+    // a step up or down costs against a step across
+    const OVER_OR_UNDER: &str = "###########\n##-------##\n##-#####-##\n##--S#G--##\n####-#-####\n####-#-####\n####-#-####\n####---####\n###########";
+
+    #[test]
+    fn dearer_vertical_steps_change_the_route() {
+        let mut grid: Grid<Cell> = OVER_OR_UNDER.parse().unwrap();
+        let SolveOutcome::Found { path, cost, .. } = solve(&mut grid) else {
+            panic!("no route between S and G");
+        };
+        assert!(path.contains(&Coordinate { x: 5, y: 7 }));
+        assert_eq!(cost, 10);
+        grid.set_step_costs(1, 3);
+        let SolveOutcome::Found { path, cost, .. } = solve(&mut grid) else {
+            panic!("no route between S and G");
+        };
+        assert!(path.contains(&Coordinate { x: 5, y: 1 }));
+        assert_eq!(cost, 22);
+        // The heuristic counts across and up and down separately, so it still never overestimates, and A* agrees with Dijkstra
+        let (dijkstra, _) = solve_with(&mut grid, Algorithm::Dijkstra, Connectivity::Four, Heuristic::Zero, None);
+        assert_eq!(dijkstra.unwrap().cost, 22);
+        grid.set_step_costs(3, 1);
+        let SolveOutcome::Found { path, cost, .. } = solve(&mut grid) else {
+            panic!("no route between S and G");
+        };
+        assert!(path.contains(&Coordinate { x: 5, y: 7 }));
+        assert_eq!(cost, 14);
+    }
+
+    #[test]
+    #[should_panic(expected = "step costs must be at least 1")]
+    fn a_step_cost_of_zero_panics() {
+        let mut grid: Grid<Cell> = OVER_OR_UNDER.parse().unwrap();
+        grid.set_step_costs(0, 1);
+    }
+
+    // An open room has a great many shortest paths, so only the tie-breaking keeps the one returned the same each time
+    #[test]
+    fn an_open_room_gives_the_same_path_every_time() {
+        let room = "#########\n--------#\n#-------#\n#-------#\n#-------#\n#--------\n#########";
+        let paths: Vec<Vec<Coordinate>> = (0..3).map(|_| {
+            let mut grid: Grid<Cell> = room.parse().unwrap();
+            let SolveOutcome::Found { path, .. } = solve(&mut grid) else {
+                panic!("the room has no way through");
+            };
+            path
+        }).collect();
+        assert!(paths.iter().all(|path| *path == paths[0]));
+        // Cells on every shortest path tie on f-score and on how close they are to the exit, so the higher one wins each time
+        assert_eq!(paths[0], coordinates(&[(0, 1), (1, 1), (2, 1), (3, 1), (4, 1), (5, 1), (6, 1), (7, 1), (7, 2), (7, 3), (7, 4), (7, 5), (8, 5)]));
+    }
+
+    // Three steps left round the edge against six straight across
+    #[test]
+    fn wrapping_round_the_edge_is_the_shorter_way() {
+        let mut grid: Grid<Cell> = "#########\n-S-----G-\n#########".parse().unwrap();
+        let SolveOutcome::Found { cost, .. } = solve(&mut grid) else {
+            panic!("no way from S to G");
+        };
+        assert_eq!(cost, 6);
+        grid.set_toroidal(true);
+        let SolveOutcome::Found { path, cost, .. } = solve(&mut grid) else {
+            panic!("no way from S to G round the edge");
+        };
+        assert_eq!((path, cost), (coordinates(&[(1, 1), (0, 1), (8, 1), (7, 1)]), 3));
+        // The heuristic measures the shorter way round too, so it never overestimates and A* still agrees with Dijkstra
+        let (dijkstra, _) = solve_with(&mut grid, Algorithm::Dijkstra, Connectivity::Four, Heuristic::Zero, None);
+        assert_eq!(dijkstra.unwrap().cost, 3);
+        assert_eq!(Heuristic::Manhattan.estimate(&grid, Coordinate { x: 1, y: 1 }, Coordinate { x: 7, y: 1 }, Connectivity::Four), 3);
+    }
+
+    // Round a pillar, over the top or underneath is the same length; BFS and DFS take whichever way the order says
+    // (BFS goes first to the neighbour listed first, and DFS, taking the last one it stacked up, to the one listed last)
+    #[test]
+    fn the_neighbour_order_picks_between_equal_paths() {
+        let over = coordinates(&[(0, 2), (1, 2), (1, 1), (2, 1), (3, 1), (3, 2), (4, 2)]);
+        let under = coordinates(&[(0, 2), (1, 2), (1, 3), (2, 3), (3, 3), (3, 2), (4, 2)]);
+        for (algorithm, order, expected) in [(Algorithm::Bfs, "NESW", &over), (Algorithm::Bfs, "SENW", &under),
+                                             (Algorithm::Dfs, "SENW", &over), (Algorithm::Dfs, "NESW", &under)] {
+            let mut grid: Grid<Cell> = "#####\n#---#\n--#--\n#---#\n#####".parse().unwrap();
+            grid.set_neighbour_order(order.parse().unwrap());
+            let (solution, _) = solve_with(&mut grid, algorithm, Connectivity::Four, Heuristic::Manhattan, None);
+            assert_eq!(&solution.unwrap().path, expected, "{:?} with the order {}", algorithm, order);
+        }
+    }
+
+    #[test]
+    fn outcomes_say_whether_the_maze_was_solved() {
+        let found = solve(&mut load_maze("maze-Easy"));
+        assert!(matches!(found, SolveOutcome::Found { cost: 26, .. }));
+        assert!(found.is_solved());
+        assert_eq!(found.stats().path_length, 27);
+        let unreachable = solve(&mut "#####\n--#--\n#####".parse().unwrap());
+        assert!(matches!(unreachable, SolveOutcome::Unreachable { .. }));
+        assert!(!unreachable.is_solved());
+        assert_eq!(unreachable.stats().path_length, 0);
+        // The entrance and the cell beside it are all the search can expand before it runs out
+        assert_eq!(unreachable.stats().nodes_expanded, 2);
+    }
+
+    // The route runs through (0, 0), which has to be followed through rather than taken for the start
+    #[test]
+    fn a_path_through_the_top_left_corner_goes_all_the_way_back() {
+        let mut grid: Grid<Cell> = "---#\nS#-#\n##G#".parse().unwrap();
+        let SolveOutcome::Found { path, cost, .. } = solve(&mut grid) else {
+            panic!("no path round the corner");
+        };
+        assert_eq!(path, coordinates(&[(0, 1), (0, 0), (1, 0), (2, 0), (2, 1), (2, 2)]));
+        assert_eq!(cost, 5);
+    }
+
+    // Parents that go round in a loop never reach a cell without one, so they're an error rather than a walk that never ends
+    #[test]
+    fn parents_going_round_in_a_loop_cant_be_followed_back() {
+        let mut grid: Grid<Cell> = "----\n----".parse().unwrap();
+        let (a, b) = (Coordinate { x: 1, y: 0 }, Coordinate { x: 2, y: 0 });
+        let (index_a, index_b) = (grid.index_of(a), grid.index_of(b));
+        grid.cells[index_a].parent_coord = Some(b);
+        grid.cells[index_b].parent_coord = Some(a);
+        assert!(matches!(path_back_from(&grid, grid.entrance_location, b), Err(SearchError::ReconstructionFailed(_))));
+    }
+
+    // Straight along the top is 6 steps; with the middle of it blocked, it's 10 round the bottom
+    const DETOUR: &str = "#######\n-------\n#-###-#\n#-----#\n#######";
+
+    #[test]
+    fn blocking_the_corridor_forces_the_detour() {
+        let mut grid: Grid<Cell> = DETOUR.parse().unwrap();
+        let blocked = HashSet::from([Coordinate { x: 3, y: 1 }]);
+        for algorithm in [Algorithm::AStar, Algorithm::Jps] {
+            let (solution, _) = solve_avoiding(&mut grid, &blocked, algorithm, Connectivity::Four, Heuristic::Manhattan, SearchLimits::default()).unwrap();
+            assert_eq!(solution.unwrap().cost, 10, "{:?}", algorithm);
+        }
+        // The grid itself is left as it was
+        assert!(matches!(solve(&mut grid), SolveOutcome::Found { cost: 6, .. }));
+    }
+
+    #[test]
+    fn blocking_both_ways_leaves_no_path() {
+        let mut grid: Grid<Cell> = DETOUR.parse().unwrap();
+        let blocked = HashSet::from([Coordinate { x: 3, y: 1 }, Coordinate { x: 3, y: 3 }]);
+        let (solution, _) = solve_avoiding(&mut grid, &blocked, Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, SearchLimits::default()).unwrap();
+        assert_eq!(solution, None);
+    }
+
+    // A maze that big won't fit in memory, but the heuristics only need the two coordinates: the far corner of the biggest
+    // maze there can be is u32::MAX each way, and the distances to it (either way round) have to come out whole
+    #[test]
+    fn heuristics_reach_the_far_corner_of_the_biggest_maze() {
+        let grid: Grid<Cell> = "---".parse().unwrap();
+        let (near, far) = (Coordinate { x: 0, y: 0 }, Coordinate { x: u32::MAX, y: u32::MAX });
+        let side = MAX_SIDE;
+        assert_eq!(Heuristic::Manhattan.estimate(&grid, near, far, Connectivity::Four), 2 * side);
+        assert_eq!(Heuristic::Manhattan.estimate(&grid, far, near, Connectivity::Four), 2 * side);
+        assert_eq!(Heuristic::Chebyshev.estimate(&grid, far, near, Connectivity::Four), side);
+        assert_eq!(Heuristic::Octile.estimate(&grid, near, far, Connectivity::Eight), 14 * side);
+        assert_eq!(Coordinate::new(side, side), far);
+    }
+
+    // The wall in the way leaves the exit out of reach, so the path goes as far as it can towards it instead, passing the side turning
+    #[test]
+    fn best_effort_heads_as_close_to_a_sealed_exit_as_it_can() {
+        let mut grid: Grid<Cell> = "#######\n----#--\n#-#####\n#######".parse().unwrap();
+        let (solution, _) = solve_best_effort(&mut grid, Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, SearchLimits::default()).unwrap();
+        assert_eq!(solution.path, coordinates(&[(0, 1), (1, 1), (2, 1), (3, 1)]));
+        assert_eq!((solution.exit, solution.cost), (Coordinate { x: 3, y: 1 }, 3));
+    }
+
+    #[test]
+    fn best_effort_reaches_an_exit_when_there_is_a_way() {
+        let mut grid = load_maze("maze-Easy");
+        let (solution, _) = solve_best_effort(&mut grid, Algorithm::Jps, Connectivity::Four, Heuristic::Manhattan, SearchLimits::default()).unwrap();
+        assert_eq!((solution.exit, solution.cost), (Coordinate { x: 18, y: 9 }, 26));
+    }
+
+    // Cut the chain of parents halfway along a real search's path: following it back stops short of the entrance, which is
+    // reported as the cell it stopped at rather than returned as a path that starts in the middle of the maze
+    #[test]
+    fn a_broken_chain_of_parents_is_an_error() {
+        let mut grid = load_maze("maze-Easy");
+        let SolveOutcome::Found { path, exit, .. } = solve(&mut grid) else {
+            panic!("maze-Easy has no solution");
+        };
+        assert_eq!(path_back_from(&grid, grid.entrance_location, exit), Ok(path.clone()));
+        let index = grid.index_of(path[10]);
+        grid.cells[index].parent_coord = None;
+        assert_eq!(path_back_from(&grid, grid.entrance_location, exit), Err(SearchError::ReconstructionFailed(path[10])));
+        // Pointing it back the way it came makes a loop instead, which mustn't be followed round forever
+        grid.cells[index].parent_coord = Some(path[11]);
+        assert!(matches!(path_back_from(&grid, grid.entrance_location, exit), Err(SearchError::ReconstructionFailed(_))));
+    }
+
+    // Three cells along the corridor, the search has to stop; the furthest it got is one step beyond the last it expanded
+    #[test]
+    fn hitting_the_visited_cap_gives_a_partial_path() {
+        let mut grid: Grid<Cell> = "#########\n---------\n#########".parse().unwrap();
+        let outcome = solve_with_max_visited(&mut grid, Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, 3).unwrap();
+        let SolveOutcome::Partial { path, cost, reason, stats } = outcome.clone() else {
+            panic!("expected a partial path, got {:?}", outcome);
+        };
+        assert_eq!(path, coordinates(&[(0, 1), (1, 1), (2, 1), (3, 1)]));
+        assert_eq!(cost, 3);
+        assert!(matches!(reason, SearchError::NodeLimitExceeded(_)));
+        assert_eq!(stats.nodes_expanded, 3);
+        // The same cap stops in the same place every time
+        let again = solve_with_max_visited(&mut grid, Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, 3).unwrap();
+        assert!(matches!(again, SolveOutcome::Partial { path: ref again_path, .. } if *again_path == path));
+        assert!(matches!(solve_with_max_visited(&mut grid, Algorithm::Jps, Connectivity::Four, Heuristic::Manhattan, 100).unwrap(),
+                         SolveOutcome::Found { cost: 8, .. }));
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use crate::{grid::{Cell, Grid}, solve::{solve_with_limits, Algorithm, Connectivity, Heuristic, SearchError, SearchLimits, SearchStats, Solution}};
+
+// Everything that decides how a maze gets solved, set up a piece at a time instead of passed to solve_with_limits in order:
+//
+//     let (solution, stats) = MazeSolver::new()
+//         .algorithm(Algorithm::AStar)
+//         .heuristic(Heuristic::Octile)
+//         .connectivity(Connectivity::Eight)
+//         .timeout(Duration::from_secs(1))
+//         .solve(&mut grid)?;
+//
+// Anything left unset is what solve uses: A* moving four ways, with no limits, and the heuristic that suits the connectivity
+// (the tests below run this example, and a few other setups)
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct MazeSolver {
+    algorithm: Option<Algorithm>,
+    heuristic: Option<Heuristic>,
+    connectivity: Option<Connectivity>,
+    limits: SearchLimits,
+}
+impl MazeSolver {
+    pub fn new() -> MazeSolver {
+        MazeSolver::default()
+    }
+
+    pub fn algorithm(mut self, algorithm: Algorithm) -> MazeSolver {
+        self.algorithm = Some(algorithm);
+        self
+    }
+
+    // Weighted A*: shorthand for .algorithm(Algorithm::WeightedAStar(weight)), for a weight of at least 1
+    pub fn weight(self, weight: f64) -> MazeSolver {
+        self.algorithm(Algorithm::WeightedAStar(weight))
+    }
+
+    pub fn heuristic(mut self, heuristic: Heuristic) -> MazeSolver {
+        self.heuristic = Some(heuristic);
+        self
+    }
+
+    pub fn connectivity(mut self, connectivity: Connectivity) -> MazeSolver {
+        self.connectivity = Some(connectivity);
+        self
+    }
+
+    // Give up once the search has expanded this many cells
+    pub fn max_nodes(mut self, max_nodes: usize) -> MazeSolver {
+        self.limits.max_nodes = Some(max_nodes);
+        self
+    }
+
+    // Give up once the search has run this long
+    pub fn timeout(mut self, timeout: Duration) -> MazeSolver {
+        self.limits.timeout = Some(timeout);
+        self
+    }
+
+    // Solve grid from its entrance to its nearest exit, as solve_with_limits does
+    pub fn solve(&self, grid: &mut Grid<Cell>) -> Result<(Option<Solution>, SearchStats), SearchError> {
+        let connectivity = self.connectivity.unwrap_or(Connectivity::Four);
+        let heuristic = self.heuristic.unwrap_or(Heuristic::default_for(connectivity));
+        solve_with_limits(grid, self.algorithm.unwrap_or(Algorithm::AStar), connectivity, heuristic, self.limits, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_maze, solve::solve_with};
+
+    // An open room, where moving diagonally cuts the corner
+    const ROOM: &str = "######\n-----#\n#----#\n#----#\n#-----\n######";
+
+    #[test]
+    fn the_example_at_the_top_solves() {
+        let mut grid: Grid<Cell> = ROOM.parse().unwrap();
+        let (solution, _) = MazeSolver::new()
+            .algorithm(Algorithm::AStar)
+            .heuristic(Heuristic::Octile)
+            .connectivity(Connectivity::Eight)
+            .timeout(Duration::from_secs(1))
+            .solve(&mut grid)
+            .unwrap();
+        // A step in, three diagonals and a step out, with costs scaled by 10 when moving eight ways
+        assert_eq!(solution.unwrap().cost, 10 + 3 * 14 + 10);
+    }
+
+    #[test]
+    fn dijkstra_moving_eight_ways_matches_solve_with() {
+        let grid = load_maze("maze-Medium");
+        let (solution, _) = MazeSolver::new().algorithm(Algorithm::Dijkstra).connectivity(Connectivity::Eight).solve(&mut grid.clone()).unwrap();
+        let (expected, _) = solve_with(&mut grid.clone(), Algorithm::Dijkstra, Connectivity::Eight, Heuristic::Octile, None);
+        assert_eq!(solution.unwrap().cost, expected.unwrap().cost);
+    }
+
+    #[test]
+    fn unset_options_are_the_defaults() {
+        let (solution, _) = MazeSolver::new().solve(&mut load_maze("maze-Easy")).unwrap();
+        assert_eq!(solution.unwrap().cost, 26);
+    }
+
+    #[test]
+    fn max_nodes_gives_up() {
+        let result = MazeSolver::new().weight(1.5).max_nodes(10).solve(&mut load_maze("maze-Medium"));
+        assert!(matches!(result, Err(SearchError::NodeLimitExceeded(stats)) if stats.nodes_expanded == 10));
+    }
+}
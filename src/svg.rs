@@ -0,0 +1,64 @@
+use std::fmt::Write;
+
+use crate::grid::{Cell, CellType, Coordinate, Grid};
+
+const WALL_COLOUR: &str = "black";
+const PATH_COLOUR: &str = "rgb(220,30,30)";
+const ENTRANCE_COLOUR: &str = "rgb(30,180,30)";
+const EXIT_COLOUR: &str = "rgb(30,60,220)";
+
+// Draw the maze and its solution as an SVG image, one unit per cell, so it scales to any size without a PNG library
+// Every wall is a 1x1 <rect>, the path is a <polyline> through the middle of each of its cells, and the entrance and
+// exits are circles (green and blue). The viewBox is the grid's own width and height
+pub fn to_svg(grid: &Grid<Cell>, path: &[Coordinate]) -> String {
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">\n",
+        grid.width, grid.height
+    );
+    for cell in grid.cells.iter().filter(|cell| cell.cell_type == CellType::Wall) {
+        // Writing to a String can't fail
+        let _ = writeln!(svg, "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"{}\"/>", cell.coordinate.x, cell.coordinate.y, WALL_COLOUR);
+    }
+    if !path.is_empty() {
+        let points: Vec<String> = path.iter().map(|point| format!("{}.5,{}.5", point.x, point.y)).collect();
+        let _ = writeln!(
+            svg,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"0.4\" stroke-linejoin=\"round\" stroke-linecap=\"round\"/>",
+            points.join(" "),
+            PATH_COLOUR
+        );
+    }
+    let openings = [(grid.entrance_location, ENTRANCE_COLOUR)].into_iter().chain(grid.exits.iter().map(|&exit| (exit, EXIT_COLOUR)));
+    for (opening, colour) in openings {
+        let _ = writeln!(svg, "<circle cx=\"{}.5\" cy=\"{}.5\" r=\"0.4\" fill=\"{}\"/>", opening.x, opening.y, colour);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_maze, solve::{solve, SolveOutcome}};
+
+    #[test]
+    fn every_wall_gets_a_rect() {
+        let mut grid = load_maze("maze-Easy");
+        let walls = grid.cells.iter().filter(|cell| cell.cell_type == CellType::Wall).count();
+        let SolveOutcome::Found { path, .. } = solve(&mut grid) else {
+            panic!("maze-Easy has no solution");
+        };
+        let svg = to_svg(&grid, &path);
+        assert_eq!(svg.matches("<rect ").count(), walls);
+        assert!(svg.contains("viewBox=\"0 0 20 10\""));
+        assert_eq!(svg.matches("<polyline ").count(), 1);
+        assert_eq!(svg.matches("<circle ").count(), 2);
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn no_path_means_no_polyline() {
+        let grid = load_maze("maze-Easy");
+        assert!(!to_svg(&grid, &[]).contains("<polyline"));
+    }
+}
@@ -0,0 +1,178 @@
+use std::{collections::{BinaryHeap, HashMap}, time::Instant};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, solve::{Connectivity, SearchStats, Solution}};
+
+// Theta* measures distances as straight lines rather than in steps, so costs are kept in thousandths of a cell
+// to stay in whole numbers like the rest of the solvers
+const SCALE: f64 = 1000.0;
+
+// The straight-line distance between two coordinates, in thousandths of a cell
+fn scaled_distance(from: Coordinate, to: Coordinate) -> usize {
+    (euclidean_distance(from, to) * SCALE).round() as usize
+}
+
+fn euclidean_distance(from: Coordinate, to: Coordinate) -> f64 {
+    let dx = from.x.abs_diff(to.x) as f64;
+    let dy = from.y.abs_diff(to.y) as f64;
+    (dx * dx + dy * dy).sqrt()
+}
+
+// The length of a path measured in straight lines between its points, in cells
+// For comparing Theta*'s turning points with the step-by-step paths the other solvers return
+pub fn euclidean_length(path: &[Coordinate]) -> f64 {
+    path.windows(2).map(|step| euclidean_distance(step[0], step[1])).sum()
+}
+
+// Whether a straight line from one coordinate to another only crosses open cells
+// Walks the line with Bresenham's algorithm; where it steps diagonally, both the cells beside the step have to be open too,
+// so a line can't slip between two walls that only touch at their corners, just as a diagonal step can't
+// One-way cells block lines as walls do, since a line could cross one any way at all; paths go through them a step at a time
+pub fn line_of_sight(grid: &Grid<Cell>, from: Coordinate, to: Coordinate) -> bool {
+    let is_open = |x: isize, y: isize| {
+        let cell = grid[Coordinate::new(x as usize, y as usize)];
+        cell.cell_type != CellType::Wall && cell.direction.is_none()
+    };
+    let (mut x, mut y) = (from.x as isize, from.y as isize);
+    let (to_x, to_y) = (to.x as isize, to.y as isize);
+    let dx = (to_x - x).abs();
+    let dy = -(to_y - y).abs();
+    let step_x = if x < to_x { 1 } else { -1 };
+    let step_y = if y < to_y { 1 } else { -1 };
+    let mut error = dx + dy;
+    loop {
+        if !is_open(x, y) {
+            return false;
+        }
+        if x == to_x && y == to_y {
+            return true;
+        }
+        let doubled_error = 2 * error;
+        let move_x = doubled_error >= dy;
+        let move_y = doubled_error <= dx;
+        if move_x && move_y && !(is_open(x + step_x, y) && is_open(x, y + step_y)) {
+            return false;
+        }
+        if move_x {
+            error += dy;
+            x += step_x;
+        }
+        if move_y {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+// Any-angle pathfinding: A* over the eight-connected grid, except that whenever a cell can see its parent's parent,
+// it's joined straight to it instead, cutting the corner. The result follows straight lines at any angle rather than
+// the staircases grid moves make, which suits robots and the like that can move in any direction
+// The path returned is just the turning points, entrance first and exit last; every straight line between two
+// consecutive points crosses only open cells. Distances are geometric, so weighted terrain costs the same as any other
+// open cell, and portals are ignored
+pub fn solve_theta_star(grid: &Grid<Cell>) -> (Option<Solution>, SearchStats) {
+    let start_time = Instant::now();
+    let mut stats = SearchStats::default();
+    let mut open_set = BinaryHeap::new();
+    let mut best_costs = HashMap::new();
+    // Unlike A*, a cell's parent needn't be next to it, just in sight of it
+    let mut parents: HashMap<Coordinate, Coordinate> = HashMap::new();
+    let mut exit_reached = None;
+    // Straight-line distance to the nearest exit; nothing can get there in less
+    let estimate = |coordinate: Coordinate| grid.exits.iter().map(|&exit| scaled_distance(coordinate, exit)).min().unwrap_or(0);
+
+    best_costs.insert(grid.entrance_location, 0);
+    open_set.push(grid[grid.entrance_location]);
+    while let Some(current_cell) = open_set.pop() {
+        if current_cell.cost > best_costs[&current_cell.coordinate] {
+            continue;
+        }
+        if grid.exits.contains(&current_cell.coordinate) {
+            exit_reached = Some(current_cell.coordinate);
+            break;
+        }
+        stats.nodes_expanded += 1;
+        let current = current_cell.coordinate;
+        let parent = parents.get(&current).copied();
+        for neighbour in grid.neighbours(current, Connectivity::Eight) {
+            let mut neighbour_cell = grid[neighbour];
+            // Portal partners aren't next to us, and a line to them would go through the maze rather than the portal
+            let adjacent = neighbour.x.abs_diff(current.x) <= 1 && neighbour.y.abs_diff(current.y) <= 1;
+            if !adjacent || neighbour_cell.cell_type == CellType::Wall || neighbour == grid.entrance_location {
+                continue;
+            }
+            // If the neighbour can see our parent, skip over us and go straight there; otherwise step from us as A* would
+            let (via, tentative_cost) = match parent {
+                Some(parent) if line_of_sight(grid, parent, neighbour) => (parent, best_costs[&parent] + scaled_distance(parent, neighbour)),
+                _ => (current, current_cell.cost + scaled_distance(current, neighbour)),
+            };
+            if best_costs.get(&neighbour).is_some_and(|&best_cost| tentative_cost >= best_cost) {
+                continue;
+            }
+            best_costs.insert(neighbour, tentative_cost);
+            parents.insert(neighbour, via);
+            neighbour_cell.cost = tentative_cost;
+            neighbour_cell.h = estimate(neighbour);
+            neighbour_cell.f_score = neighbour_cell.cost + neighbour_cell.h;
+            open_set.push(neighbour_cell);
+        }
+        stats.max_frontier_size = stats.max_frontier_size.max(open_set.len());
+    }
+
+    let Some(exit) = exit_reached else {
+        stats.elapsed = start_time.elapsed();
+        return (None, stats);
+    };
+    let mut path = vec![exit];
+    while let Some(&parent) = parents.get(path.last().unwrap()) {
+        path.push(parent);
+    }
+    path.reverse();
+    // This counts turning points rather than cells, since that's what the path holds
+    stats.path_length = path.len();
+    stats.elapsed = start_time.elapsed();
+    // The cost is the straight-line length in thousandths of a cell, like every other cost here
+    (Some(Solution { path, exit, cost: best_costs[&exit] }), stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_maze, solve::{solve_with, Algorithm, Heuristic}};
+
+    // Theta*'s straight lines are never longer than the staircase four-way A* walks, however winding the maze
+    fn no_longer_than_astar(grid: &Grid<Cell>) -> (f64, f64) {
+        let (theta, _) = solve_theta_star(grid);
+        let (astar, _) = solve_with(&mut grid.clone(), Algorithm::AStar, Connectivity::Four, Heuristic::Manhattan, None);
+        let (theta, astar) = (theta.unwrap(), astar.unwrap());
+        assert!(theta.path.windows(2).all(|line| line_of_sight(grid, line[0], line[1])));
+        let (theta_length, astar_length) = (euclidean_length(&theta.path), euclidean_length(&astar.path));
+        assert!(theta_length <= astar_length, "Theta*'s path is {} long and A*'s {}", theta_length, astar_length);
+        (theta_length, astar_length)
+    }
+
+    #[test]
+    fn cuts_straight_across_an_open_room() {
+        let grid: Grid<Cell> = "########\n-------#\n#------#\n#------#\n#------#\n#-------\n########".parse().unwrap();
+        let (theta_length, astar_length) = no_longer_than_astar(&grid);
+        assert_eq!(astar_length, 11.0);
+        // Straight from one corner to the other would be about 8.06 long; the way in and out through the walls adds a little
+        assert!(theta_length < 8.5, "{}", theta_length);
+    }
+
+    #[test]
+    fn is_no_longer_than_astar_through_a_maze() {
+        no_longer_than_astar(&load_maze("maze-Medium"));
+    }
+
+    #[test]
+    fn lines_of_sight_stop_at_walls_and_between_corners() {
+        let grid: Grid<Cell> = "#####\n----#\n#-#-#\n#----\n#####".parse().unwrap();
+        let at = |x, y| Coordinate { x, y };
+        assert!(line_of_sight(&grid, at(1, 1), at(3, 1)));
+        assert!(!line_of_sight(&grid, at(1, 2), at(3, 2)));
+        assert!(!line_of_sight(&grid, at(1, 1), at(3, 3)));
+        // Two walls that only touch at their corners still block a line between them
+        let grid: Grid<Cell> = "####\n--##\n##--\n####".parse().unwrap();
+        assert!(!line_of_sight(&grid, at(1, 1), at(2, 2)));
+    }
+}
@@ -0,0 +1,139 @@
+use std::{cmp::Reverse, collections::{BinaryHeap, HashMap}, time::Instant};
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, solve::{Connectivity, Heuristic, SearchError, SearchLimits, SearchStats, Solution}};
+
+// Where a search over turns has got to: a cell, and the direction of the step that arrived there
+// The entrance has no direction, and neither does a cell reached through a portal, so the step after either is never a turn
+type State = (Coordinate, Option<(isize, isize)>);
+
+// A* that charges penalty on top of the usual cost for every step that changes direction, for paths with fewer bends
+// Reaching a cell heading one way isn't the same as reaching it heading another, as only one of them makes the next step
+// straight on, so the search is over (cell, direction) pairs: four or eight times as many states as cells. Penalties only
+// ever add to a route's cost, so the heuristic stays admissible, and the path found is the cheapest counting its turns;
+// with a penalty of 0 that's just the cheapest path. Solution::cost is what the path costs without its penalties, the same
+// as for any other path, so it's easy to see how much longer fewer bends have made it
+pub fn solve_with_turn_penalty(grid: &Grid<Cell>, connectivity: Connectivity, heuristic: Heuristic, penalty: usize, limits: SearchLimits)
+                               -> Result<(Option<Solution>, SearchStats), SearchError> {
+    let start_time = Instant::now();
+    let mut stats = SearchStats::default();
+    let start: State = (grid.entrance_location, None);
+    let estimate = |coordinate: Coordinate| heuristic.estimate_nearest(grid, coordinate, &grid.exits, connectivity);
+    let h = estimate(start.0);
+    // Ordered like Cells: lowest f-score first, ties going to the lowest heuristic and then to the top left
+    let mut open_set = BinaryHeap::from([Reverse((h, h, start.0.y, start.0.x, start.1))]);
+    let mut best_costs = HashMap::from([(start, 0)]);
+    let mut parents: HashMap<State, State> = HashMap::new();
+    let mut exit_reached = None;
+    while let Some(Reverse((f_score, h, y, x, heading))) = open_set.pop() {
+        let state = (Coordinate{x, y}, heading);
+        let cost = best_costs[&state];
+        // A stale copy left behind when a cheaper route to this state was found
+        if f_score > cost + h {
+            continue;
+        }
+        let current = state.0;
+        if grid.exits.contains(&current) {
+            exit_reached = Some(state);
+            break;
+        }
+        limits.check(&stats, start_time)?;
+        stats.nodes_expanded += 1;
+        for neighbour in grid.neighbours(current, connectivity) {
+            if grid[neighbour].cell_type == CellType::Wall || neighbour == start.0 {
+                continue;
+            }
+            let direction = step_direction(grid, current, neighbour);
+            let turned = heading.is_some() && direction.is_some() && heading != direction;
+            let tentative_cost = cost + connectivity.step_cost_between(grid, current, neighbour) * grid[neighbour].weight
+                + if turned { penalty } else { 0 };
+            let next = (neighbour, direction);
+            if best_costs.get(&next).is_some_and(|&best_cost| tentative_cost >= best_cost) {
+                continue;
+            }
+            best_costs.insert(next, tentative_cost);
+            parents.insert(next, state);
+            let h = estimate(neighbour);
+            open_set.push(Reverse((tentative_cost + h, h, neighbour.y, neighbour.x, direction)));
+        }
+        stats.max_frontier_size = stats.max_frontier_size.max(open_set.len());
+    }
+    let Some(exit_state) = exit_reached else {
+        stats.elapsed = start_time.elapsed();
+        return Ok((None, stats));
+    };
+    let mut path = vec![exit_state.0];
+    let mut state = exit_state;
+    while let Some(&parent) = parents.get(&state) {
+        path.push(parent.0);
+        state = parent;
+    }
+    path.reverse();
+    let cost = path.windows(2).map(|step| connectivity.step_cost_between(grid, step[0], step[1]) * grid[step[1]].weight).sum();
+    stats.path_length = path.len();
+    stats.elapsed = start_time.elapsed();
+    Ok((Some(Solution { path, exit: exit_state.0, cost }), stats))
+}
+
+// Which way a step from one cell to a neighbour goes, as (dx, dy) each -1, 0 or 1, or None for a jump through a portal
+// A step round the edge of a maze that wraps goes the way it would have if the maze carried on
+fn step_direction(grid: &Grid<Cell>, from: Coordinate, to: Coordinate) -> Option<(isize, isize)> {
+    if grid.portals.get(&from) == Some(&to) {
+        return None;
+    }
+    let axis = |from: usize, to: usize, size: usize| {
+        if to == from {
+            0
+        }
+        else if to == (from + 1) % size {
+            1
+        }
+        else {
+            -1
+        }
+    };
+    Some((axis(from.x as usize, to.x as usize, grid.width), axis(from.y as usize, to.y as usize, grid.height)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_maze, solve::{solve, SolveOutcome}};
+
+    // The shortest way is down the staircase, 13 steps with 8 bends; round by the left and bottom is 15 steps with 3
+    const STAIRS: &str = "\
+############
+#S--########
+#-#--#######
+#-##--######
+#-###--#####
+#-####----G#
+#---------##
+############";
+
+    fn bends(grid: &Grid<Cell>, path: &[Coordinate]) -> usize {
+        let directions: Vec<_> = path.windows(2).map(|step| step_direction(grid, step[0], step[1])).collect();
+        directions.windows(2).filter(|pair| pair[0] != pair[1]).count()
+    }
+
+    #[test]
+    fn a_higher_penalty_takes_a_longer_way_with_fewer_bends() {
+        let grid: Grid<Cell> = STAIRS.parse().unwrap();
+        let solve_penalised = |penalty| {
+            solve_with_turn_penalty(&grid, Connectivity::Four, Heuristic::Manhattan, penalty, SearchLimits::default()).unwrap().0.unwrap()
+        };
+        let (straight, smooth) = (solve_penalised(0), solve_penalised(5));
+        assert_eq!((straight.cost, bends(&grid, &straight.path)), (13, 8));
+        assert_eq!((smooth.cost, bends(&grid, &smooth.path)), (15, 3));
+        assert_eq!(grid.validate_path(&smooth.path, Connectivity::Four), Ok(15));
+    }
+
+    #[test]
+    fn no_penalty_costs_the_same_as_astar() {
+        let mut grid = load_maze("maze-Medium");
+        let (solution, _) = solve_with_turn_penalty(&grid, Connectivity::Four, Heuristic::Manhattan, 0, SearchLimits::default()).unwrap();
+        let SolveOutcome::Found { cost, .. } = solve(&mut grid) else {
+            panic!("maze-Medium has no solution");
+        };
+        assert_eq!(solution.unwrap().cost, cost);
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use crate::{grid::{Cell, CellType, Coordinate, Grid}, solve::Connectivity};
+
+// Why a path isn't a route through the maze
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PathError {
+    Empty,
+    // The path doesn't start at the entrance, or doesn't finish at an exit
+    WrongStart(Coordinate),
+    WrongEnd(Coordinate),
+    OutOfBounds(Coordinate),
+    InWall(Coordinate),
+    // Two coordinates next to each other in the path that a single step can't get between
+    NotAdjacent(Coordinate, Coordinate),
+}
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::Empty => write!(f, "path is empty"),
+            PathError::WrongStart(coordinate) => write!(f, "path starts at {}, not the entrance", coordinate),
+            PathError::WrongEnd(coordinate) => write!(f, "path ends at {}, which isn't an exit", coordinate),
+            PathError::OutOfBounds(coordinate) => write!(f, "path goes through {}, which is outside the maze", coordinate),
+            PathError::InWall(coordinate) => write!(f, "path goes through {}, which is a wall", coordinate),
+            PathError::NotAdjacent(from, to) => write!(f, "path jumps from {} to {}, which aren't a step apart", from, to),
+        }
+    }
+}
+impl std::error::Error for PathError {}
+
+impl Grid<Cell> {
+    // Check that path is a route through the maze: from the entrance to an exit, through open cells only,
+    // with every step one that connectivity allows (through a portal or round the edge of a toroidal maze included)
+    // Returns what the path costs, the same way the solvers count it, so it can check a path from anywhere,
+    // including one the solver found
+    pub fn validate_path(&self, path: &[Coordinate], connectivity: Connectivity) -> Result<usize, PathError> {
+        let (Some(&first), Some(&last)) = (path.first(), path.last()) else {
+            return Err(PathError::Empty);
+        };
+        if first != self.entrance_location {
+            return Err(PathError::WrongStart(first));
+        }
+        if !self.exits.contains(&last) {
+            return Err(PathError::WrongEnd(last));
+        }
+        for &coordinate in path {
+            match self.try_cell_at(coordinate) {
+                None => return Err(PathError::OutOfBounds(coordinate)),
+                Some(cell) if cell.cell_type == CellType::Wall => return Err(PathError::InWall(coordinate)),
+                Some(_) => (),
+            }
+        }
+        let mut cost = 0;
+        for step in path.windows(2) {
+            let (from, to) = (step[0], step[1]);
+            if !self.neighbours(from, connectivity).contains(&to) {
+                return Err(PathError::NotAdjacent(from, to));
+            }
+            cost += connectivity.step_cost_between(self, from, to) * self[to].weight;
+        }
+        Ok(cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The entrance is (0, 1) and the exit (4, 3)
+    const MAZE: &str = "#####\n---##\n#---#\n#----\n#####";
+
+    fn path(coordinates: &[(u32, u32)]) -> Vec<Coordinate> {
+        coordinates.iter().map(|&(x, y)| Coordinate { x, y }).collect()
+    }
+
+    #[test]
+    fn a_route_through_the_maze_returns_its_cost() {
+        let grid: Grid<Cell> = MAZE.parse().unwrap();
+        assert_eq!(grid.validate_path(&path(&[(0, 1), (1, 1), (1, 2), (1, 3), (2, 3), (3, 3), (4, 3)]), Connectivity::Four), Ok(6));
+    }
+
+    #[test]
+    fn the_path_has_to_start_at_the_entrance_and_end_at_an_exit() {
+        let grid: Grid<Cell> = MAZE.parse().unwrap();
+        assert_eq!(grid.validate_path(&[], Connectivity::Four), Err(PathError::Empty));
+        assert_eq!(grid.validate_path(&path(&[(1, 1), (1, 2), (1, 3), (2, 3), (3, 3), (4, 3)]), Connectivity::Four),
+                   Err(PathError::WrongStart(Coordinate { x: 1, y: 1 })));
+        assert_eq!(grid.validate_path(&path(&[(0, 1), (1, 1), (1, 2), (1, 3), (2, 3), (3, 3)]), Connectivity::Four),
+                   Err(PathError::WrongEnd(Coordinate { x: 3, y: 3 })));
+    }
+
+    #[test]
+    fn the_path_cant_go_through_a_wall() {
+        let grid: Grid<Cell> = MAZE.parse().unwrap();
+        assert_eq!(grid.validate_path(&path(&[(0, 1), (1, 1), (2, 1), (3, 1), (3, 2), (3, 3), (4, 3)]), Connectivity::Four),
+                   Err(PathError::InWall(Coordinate { x: 3, y: 1 })));
+        assert_eq!(grid.validate_path(&path(&[(0, 1), (1, 1), (1, 2), (1, 5), (4, 3)]), Connectivity::Four),
+                   Err(PathError::OutOfBounds(Coordinate { x: 1, y: 5 })));
+    }
+
+    #[test]
+    fn every_step_has_to_be_one_the_connectivity_allows() {
+        let grid: Grid<Cell> = MAZE.parse().unwrap();
+        assert_eq!(grid.validate_path(&path(&[(0, 1), (1, 1), (1, 3), (2, 3), (3, 3), (4, 3)]), Connectivity::Four),
+                   Err(PathError::NotAdjacent(Coordinate { x: 1, y: 1 }, Coordinate { x: 1, y: 3 })));
+        // A diagonal step is fine moving eight ways, but not four
+        let diagonal = path(&[(0, 1), (1, 1), (2, 2), (3, 3), (4, 3)]);
+        assert_eq!(grid.validate_path(&diagonal, Connectivity::Four), Err(PathError::NotAdjacent(Coordinate { x: 1, y: 1 }, Coordinate { x: 2, y: 2 })));
+        assert_eq!(grid.validate_path(&diagonal, Connectivity::Eight), Ok(48));
+    }
+}
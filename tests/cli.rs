@@ -0,0 +1,56 @@
+// Runs the built mazesolver binary through each of its subcommands, checking what it prints
+use std::process::{Command, Output};
+
+fn mazesolver(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_mazesolver"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap()
+}
+
+// What the command printed, having checked it succeeded
+fn stdout(args: &[&str]) -> String {
+    let output = mazesolver(args);
+    assert!(output.status.success(), "mazesolver {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn solve_prints_the_path_length_and_cost() {
+    let printed = stdout(&["solve", "mazes/maze-Easy.txt", "--quiet"]);
+    assert!(printed.contains("Path length (cells): 27"));
+    assert!(printed.contains("Path cost: 26"));
+    assert!(printed.contains("Exit reached: (18, 9)"));
+}
+
+#[test]
+fn render_draws_the_path_from_the_entrance() {
+    let printed = stdout(&["render", "mazes/maze-Easy.txt", "--quiet"]);
+    assert!(printed.contains("\n#S##################\n"));
+    assert_eq!(printed.matches('*').count(), 25);
+}
+
+#[test]
+fn analyze_describes_the_maze_without_solving_it() {
+    let printed = stdout(&["analyze", "mazes/maze-Easy.txt", "--quiet"]);
+    assert!(printed.contains("Size: 20x10"));
+    assert!(printed.contains("Openings: 2"));
+    assert!(!printed.contains("Path cost"));
+}
+
+#[test]
+fn generate_prints_the_same_maze_for_the_same_seed() {
+    let printed = stdout(&["generate", "11", "7", "--seed", "3"]);
+    assert_eq!(printed.lines().count(), 7);
+    assert_eq!(printed, stdout(&["generate", "11", "7", "--seed", "3"]));
+}
+
+// Each subcommand only takes its own options
+#[test]
+fn an_option_from_another_command_is_rejected() {
+    let output = mazesolver(&["generate", "11", "7", "--algo", "bfs"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'--algo' isn't an option of mazesolver generate"));
+}
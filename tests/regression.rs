@@ -0,0 +1,64 @@
+// Pins down how every bundled maze solves, so a change that alters a path (rather than just how quickly it's found) fails here
+// Ties between equally good cells are broken the same way every run, so A* always takes the same route; if a change means to
+// alter the routes, update the numbers below along with it
+use std::path::Path;
+
+use mazesolver::{solve, solve_with, Algorithm, Cell, Connectivity, Coordinate, Grid, Heuristic, SolveOutcome};
+
+// Each maze, with the number of cells on its shortest path from the entrance and the exit A* reaches
+// Every step costs 1 in these mazes, so the path costs one less than it has cells
+const EXPECTED: [(&str, usize, Coordinate); 5] = [
+    ("maze-Easy", 27, Coordinate { x: 18, y: 9 }),
+    ("maze-Medium", 321, Coordinate { x: 198, y: 99 }),
+    ("maze-Large", 974, Coordinate { x: 59, y: 599 }),
+    ("maze-VLarge", 3691, Coordinate { x: 1880, y: 999 }),
+    ("maze-VLarge2", 4141, Coordinate { x: 0, y: 114 }),
+];
+
+fn load(name: &str) -> Grid<Cell> {
+    let path = format!("{}/mazes/{}.txt", env!("CARGO_MANIFEST_DIR"), name);
+    Grid::new(Path::new(&path)).unwrap_or_else(|error| panic!("couldn't load {}: {}", path, error))
+}
+
+#[test]
+fn astar_paths_match_the_recorded_lengths() {
+    for (name, length, exit) in EXPECTED {
+        let mut maze = load(name);
+        let SolveOutcome::Found { path, exit: exit_reached, cost, stats } = solve(&mut maze) else {
+            panic!("{} has no solution", name);
+        };
+        assert_eq!(path.len(), length, "{} path length", name);
+        assert_eq!(stats.path_length, length, "{} stats.path_length", name);
+        assert_eq!(cost, length - 1, "{} path cost", name);
+        assert_eq!(exit_reached, exit, "{} exit reached", name);
+        assert_eq!(path.first(), Some(&maze.entrance()), "{} path start", name);
+        assert_eq!(maze.validate_path(&path, Connectivity::Four), Ok(length - 1), "{} path validity", name);
+    }
+}
+
+// The same path every time, not just the same length
+#[test]
+fn astar_paths_are_the_same_every_run() {
+    for (name, _, _) in EXPECTED {
+        let maze = load(name);
+        let first = solve(&mut maze.clone());
+        let second = solve(&mut maze.clone());
+        match (first, second) {
+            (SolveOutcome::Found { path: first, .. }, SolveOutcome::Found { path: second, .. }) => assert_eq!(first, second, "{} path", name),
+            _ => panic!("{} has no solution", name),
+        }
+    }
+}
+
+// The other searches that promise shortest paths have to agree with A* on the length, if not on the route
+#[test]
+fn optimal_algorithms_agree_on_length() {
+    for (name, length, _) in EXPECTED {
+        let maze = load(name);
+        for algorithm in [Algorithm::Dijkstra, Algorithm::Bfs, Algorithm::Jps] {
+            let (solution, _) = solve_with(&mut maze.clone(), algorithm, Connectivity::Four, Heuristic::Manhattan, None);
+            let solution = solution.unwrap_or_else(|| panic!("{} has no solution with {:?}", name, algorithm));
+            assert_eq!(solution.cost, length - 1, "{} path cost with {:?}", name, algorithm);
+        }
+    }
+}